@@ -525,8 +525,9 @@ async function startServer() {
                 return;
               }
 
-              ws.send(JSON.stringify({ type: 'auth_success', role, clientId }));
+              ws.send(JSON.stringify({ type: 'auth_success', role, clientId, serverTime: Date.now() }));
             } else {
+              ws.send(JSON.stringify({ type: 'auth_failure', reason: 'Invalid secret' }));
               ws.close(1008, 'Invalid secret');
             }
             return;
@@ -538,7 +539,7 @@ async function startServer() {
 
         // Handle messages from authenticated clients
         if (message.type === 'ping') {
-          ws.send(JSON.stringify({ type: 'pong' }));
+          ws.send(JSON.stringify({ type: 'pong', serverTime: Date.now() }));
           return;
         }
 
@@ -570,6 +571,15 @@ async function startServer() {
             return;
           }
 
+          // Intermediate progress for a pending control request; the request stays pending
+          if (message.type === 'control_progress') {
+            const pending = pendingControl.get(message.id);
+            if (pending && pending.replyTo?.readyState === 1) {
+              pending.replyTo.send(JSON.stringify(message));
+            }
+            return;
+          }
+
           // Handle control requests originating from bridge -> forward to consumers
           if (message.type === 'control_request') {
             const serialized = JSON.stringify(message);
@@ -595,6 +605,29 @@ async function startServer() {
             return;
           }
 
+          // Bridge-initiated requests: forward to consumers; their bridge_result is routed back
+          if (message.type === 'bridge_request') {
+            const serialized = JSON.stringify(message);
+            let delivered = 0;
+            consumers.forEach((consumer) => {
+              if (consumer.readyState === 1) {
+                consumer.send(serialized);
+                delivered += 1;
+              }
+            });
+            if (delivered > 0) {
+              pendingControl.set(message.id, { replyTo: ws, origin: 'bridge' });
+            } else {
+              ws.send(JSON.stringify({
+                type: 'bridge_result',
+                id: message.id,
+                ok: false,
+                error: { message: 'No consumers connected', code: 'UNAVAILABLE' },
+              }));
+            }
+            return;
+          }
+
           // Handle screenshot events with rate limiting
           if (message.type === 'screenshot') {
             const bridgeCaps = bridgeCapabilities.get(ws);
@@ -752,7 +785,20 @@ async function startServer() {
             return;
           }
 
-          if (message.type === 'control_result') {
+          // Cancel a forwarded control request; the bridge answers with a cancelled control_result
+          if (message.type === 'control_cancel' && pendingControl.has(message.id)) {
+            const payload = { type: 'control_cancel', id: message.id };
+            bridges.forEach((bridgeWs) => {
+              if (bridgeWs.kind === 'http') {
+                bridgeWs.queue.push(payload);
+              } else if (bridgeWs.readyState === 1) {
+                bridgeWs.send(JSON.stringify(payload));
+              }
+            });
+            return;
+          }
+
+          if (message.type === 'control_result' || message.type === 'bridge_result') {
             const pending = pendingControl.get(message.id);
             if (pending && pending.replyTo?.readyState === 1) {
               pending.replyTo.send(JSON.stringify(message));
@@ -0,0 +1,32 @@
+use std::process::Command;
+
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|v| v.trim().to_string())
+        .unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=ARIA_BRIDGE_RUSTC_VERSION={}", version);
+    println!("cargo:rerun-if-env-changed=RUSTC");
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Regenerate `include/aria_bridge.h` from `src/ffi.rs`.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let config = cbindgen::Config::from_file(format!("{dir}/cbindgen.toml")).expect("cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_src(format!("{dir}/src/ffi.rs"))
+        .with_config(config)
+        .generate()
+        .expect("generate C header")
+        .write_to_file(format!("{dir}/include/aria_bridge.h"));
+}
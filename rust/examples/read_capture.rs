@@ -0,0 +1,21 @@
+//! Print a wire capture written via `BridgeConfig::wire_capture`:
+//! `cargo run --example read_capture -- bridge.ariacap`
+use aria_bridge_client::{CaptureReader, Direction, FrameKind};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).ok_or("usage: read_capture <file>")?;
+    for record in CaptureReader::open(path)? {
+        let record = record?;
+        let arrow = if record.direction == Direction::Sent { "->" } else { "<-" };
+        let body = match record.kind {
+            FrameKind::Close if record.payload.len() >= 2 => {
+                let code = u16::from_be_bytes([record.payload[0], record.payload[1]]);
+                format!("{} {}", code, String::from_utf8_lossy(&record.payload[2..]))
+            }
+            FrameKind::Binary | FrameKind::Ping | FrameKind::Pong => format!("{} bytes {:02x?}", record.payload.len(), record.payload),
+            _ => String::from_utf8_lossy(&record.payload).into_owned(),
+        };
+        println!("{} {} {:?} {}", record.timestamp_ms, arrow, record.kind, body);
+    }
+    Ok(())
+}
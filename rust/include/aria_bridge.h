@@ -0,0 +1,72 @@
+#ifndef ARIA_BRIDGE_H
+#define ARIA_BRIDGE_H
+
+/* Generated by cbindgen from src/ffi.rs; build with `--features ffi` to update. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Opaque client handle returned by [`aria_bridge_new`].
+ */
+typedef struct AriaBridge AriaBridge;
+
+/**
+ * Handles a control request given as JSON (`{type, id, action, args, ...}`) and returns the
+ * result as JSON (a bare string is sent as a JSON string), or NULL to fail the request.
+ * The returned string is copied before the callback's next call and must stay valid until then.
+ */
+typedef const char *(*AriaControlCallback)(const char *request_json, void *user_data);
+
+/**
+ * Connect to `url` with `secret`, tagging the session with `project_id` (may be NULL).
+ * Returns NULL if an argument is not valid UTF-8 or the runtime can't start. Release the
+ * handle with [`aria_bridge_shutdown`].
+ *
+ * # Safety
+ * `url` and `secret` must be valid NUL-terminated strings; `project_id` must be one or NULL.
+ */
+struct AriaBridge *aria_bridge_new(const char *url, const char *secret, const char *project_id);
+
+/**
+ * Enqueue a `console` event.
+ *
+ * # Safety
+ * `bridge` must come from [`aria_bridge_new`] and not be shut down; `level` and `message`
+ * must be valid NUL-terminated strings.
+ */
+int aria_bridge_send_console(struct AriaBridge *bridge, const char *level, const char *message);
+
+/**
+ * Enqueue an `error` event.
+ *
+ * # Safety
+ * As for [`aria_bridge_send_console`].
+ */
+int aria_bridge_send_error(struct AriaBridge *bridge, const char *message);
+
+/**
+ * Answer control requests with `callback`, which runs on the bridge thread and gets
+ * `user_data` back unchanged. Replaces any earlier callback.
+ *
+ * # Safety
+ * `bridge` must be a live handle; `callback` must be safe to call from another thread with
+ * `user_data` until the bridge is shut down.
+ */
+int aria_bridge_on_control(struct AriaBridge *bridge,
+                           AriaControlCallback callback,
+                           void *user_data);
+
+/**
+ * Flush, say goodbye, close, and free the handle, waiting up to `timeout_ms`. On timeout
+ * (-1) the handle is still freed and the bridge thread finishes in the background.
+ *
+ * # Safety
+ * `bridge` must come from [`aria_bridge_new`]; it is invalid after this call.
+ */
+int aria_bridge_shutdown(struct AriaBridge *bridge, uint64_t timeout_ms);
+
+#endif  /* ARIA_BRIDGE_H */
@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+
+use serde_json::Value;
+
+use crate::{now_ms, BridgeClient};
+
+/// Events sent but not yet acknowledged, in send order.
+#[derive(Default)]
+pub(crate) struct AckState {
+    next_seq: u64,
+    last_acked: Option<u64>,
+    in_flight: VecDeque<(u64, u64, Value)>,
+}
+
+/// Delivery progress reported by [`BridgeClient::sync_status`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncStatus {
+    /// Highest `seq` the host has acknowledged.
+    pub last_acked_seq: Option<u64>,
+    /// Events sent but not yet acknowledged.
+    pub in_flight: usize,
+    /// Events still waiting in the buffer.
+    pub buffered: usize,
+    /// Age of the oldest unacknowledged event, in ms (0 when everything is acknowledged).
+    pub lag_ms: u64,
+}
+
+impl SyncStatus {
+    /// Nothing is buffered or waiting for an ack.
+    pub fn is_synced(&self) -> bool {
+        self.in_flight == 0 && self.buffered == 0
+    }
+}
+
+impl BridgeClient {
+    /// With `acks` enabled, how far the host has confirmed delivery; use it to decide
+    /// whether it's safe to exit.
+    pub fn sync_status(&self) -> SyncStatus {
+        let buffered = self.buffer.lock().unwrap().len();
+        let acks = self.acks.lock().unwrap();
+        SyncStatus {
+            last_acked_seq: acks.last_acked,
+            in_flight: acks.in_flight.len(),
+            buffered,
+            lag_ms: acks.in_flight.front().map_or(0, |(_, sent, _)| now_ms().saturating_sub(*sent)),
+        }
+    }
+
+    /// Whether sent events are kept until acknowledged: `acks` is on and the host
+    /// negotiated a protocol with acks.
+    pub(crate) fn tracks_acks(&self) -> bool {
+        self.cfg.acks && self.batch_ack_negotiated()
+    }
+
+    /// Number outgoing events with `seq` and remember them until acknowledged. Tracking is
+    /// capped at `buffer_limit`; beyond that the oldest are forgotten and counted as dropped.
+    /// Off unless `tracks_acks`.
+    pub(crate) fn track_unacked(&self, events: &mut [Value]) {
+        if !self.tracks_acks() {
+            return;
+        }
+        let mut acks = self.acks.lock().unwrap();
+        let now = now_ms();
+        for ev in events.iter_mut() {
+            let seq = match ev.get("seq").and_then(|s| s.as_u64()) {
+                Some(seq) => seq,
+                None => {
+                    acks.next_seq += 1;
+                    ev["seq"] = Value::from(acks.next_seq);
+                    acks.next_seq
+                }
+            };
+            acks.in_flight.push_back((seq, now, ev.clone()));
+        }
+        let overflow = acks.in_flight.len().saturating_sub(self.cfg.buffer_limit);
+        if overflow > 0 {
+            let mut stats = self.stats.lock().unwrap();
+            for (_, _, ev) in acks.in_flight.drain(..overflow) {
+                stats.dropped(&ev);
+                self.pending_log.remove([&ev]);
+            }
+        }
+    }
+
+    /// `{type:"ack", seq}` acknowledges every event up to and including `seq`.
+    pub(crate) fn handle_ack(&self, seq: u64) {
+        let acked: VecDeque<_> = {
+            let mut acks = self.acks.lock().unwrap();
+            let (acked, rest) = std::mem::take(&mut acks.in_flight).into_iter().partition(|(s, _, _)| *s <= seq);
+            acks.in_flight = rest;
+            acks.last_acked = Some(acks.last_acked.map_or(seq, |last| last.max(seq)));
+            acked
+        };
+        acked.iter().for_each(|(_, _, ev)| self.reported_dumps.delivered(ev));
+        self.pending_log.remove(acked.iter().map(|(_, _, ev)| ev));
+    }
+
+    /// Unacknowledged events from a previous connection, to be sent again first.
+    pub(crate) fn take_unacked(&self) -> Vec<Value> {
+        self.acks.lock().unwrap().in_flight.drain(..).map(|(_, _, ev)| ev).collect()
+    }
+}
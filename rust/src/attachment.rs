@@ -0,0 +1,57 @@
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::{now_ms, random, BridgeClient};
+
+pub const ATTACHMENT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Image bytes or serialized UI state returned by a [`SnapshotProvider`].
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+/// Application hook invoked for `snapshot` / `screenshot` control requests.
+pub trait SnapshotProvider: Send + Sync {
+    fn snapshot(&self, args: &Value) -> Result<Snapshot, String>;
+}
+
+impl<F> SnapshotProvider for F
+where
+    F: Fn(&Value) -> Result<Snapshot, String> + Send + Sync,
+{
+    fn snapshot(&self, args: &Value) -> Result<Snapshot, String> {
+        self(args)
+    }
+}
+
+impl BridgeClient {
+    /// Queue `data` as a run of `attachment` events sharing one `transferId` and return a
+    /// descriptor the caller can embed in a control result or event.
+    pub(crate) fn stream_attachment(&self, name: &str, mime: &str, data: &[u8]) -> Value {
+        let transfer_id = format!("{:016x}", random::next_u64());
+        let chunks: Vec<&[u8]> = if data.is_empty() { vec![&[][..]] } else { data.chunks(ATTACHMENT_CHUNK_BYTES).collect() };
+        let total = chunks.len();
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            self.enqueue(json!({
+                "type":"attachment",
+                "transferId":transfer_id,
+                "name":name,
+                "mime":mime,
+                "index":index,
+                "total":total,
+                "final":index + 1 == total,
+                "data":B64.encode(chunk),
+                "timestamp":now_ms()
+            }));
+        }
+        json!({"transferId":transfer_id,"name":name,"mime":mime,"size":data.len(),"chunks":total})
+    }
+
+    pub(crate) fn take_snapshot(&self, args: &Value) -> Option<Result<Value, String>> {
+        let provider = self.snapshot_provider.lock().unwrap().clone()?;
+        Some(provider.snapshot(args).map(|shot| self.stream_attachment("snapshot", &shot.mime, &shot.data)))
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const FLAP_MIN_SESSION_MS: u64 = 5_000;
+pub const FLAP_MAX_FLAPS: u32 = 5;
+pub const FLAP_COOL_OFF_MS: u64 = 60_000;
+
+/// Computes the wait before reconnect attempt `attempt` (0 for the first retry).
+pub type BackoffFn = Arc<dyn Fn(u32) -> Duration + Send + Sync>;
+
+/// How long to wait between failed connection attempts; see [`BridgeConfig::backoff`].
+///
+/// `jitter` stretches each delay by a random factor in `1.0..1.0 + jitter` (the built-in
+/// default uses 0.5); capped policies never exceed their cap.
+///
+/// [`BridgeConfig::backoff`]: crate::BridgeConfig::backoff
+#[derive(Clone)]
+pub enum BackoffPolicy {
+    /// `initial_ms`, doubling per attempt up to `max_ms`.
+    Exponential { initial_ms: u64, max_ms: u64, jitter: f64 },
+    /// The same delay every time.
+    Fixed { delay_ms: u64, jitter: f64 },
+    /// `initial_ms` times 1, 1, 2, 3, 5, ... up to `max_ms`; grows more gently than doubling.
+    Fibonacci { initial_ms: u64, max_ms: u64, jitter: f64 },
+    /// Your own schedule, used as-is (no jitter or cap applied).
+    Custom(BackoffFn),
+}
+
+impl BackoffPolicy {
+    /// A [`BackoffPolicy::Custom`] policy from a closure.
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(u32) -> Duration + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(f))
+    }
+
+    /// The wait before retry `attempt`, with `unit` from `[0, 1)` picking the jitter.
+    pub fn delay(&self, attempt: u32, unit: f64) -> Duration {
+        let (base_ms, max_ms, jitter) = match self {
+            Self::Exponential { initial_ms, max_ms, jitter } => {
+                (initial_ms.saturating_mul(1u64 << attempt.min(32)), *max_ms, *jitter)
+            }
+            Self::Fixed { delay_ms, jitter } => (*delay_ms, u64::MAX, *jitter),
+            Self::Fibonacci { initial_ms, max_ms, jitter } => {
+                (initial_ms.saturating_mul(fibonacci(attempt)), *max_ms, *jitter)
+            }
+            Self::Custom(f) => return f(attempt),
+        };
+        let base = Duration::from_millis(base_ms.min(max_ms));
+        let factor = 1.0 + jitter.max(0.0) * unit.clamp(0.0, 1.0);
+        std::cmp::min(base.mul_f64(factor), Duration::from_millis(max_ms))
+    }
+}
+
+impl fmt::Debug for BackoffPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exponential { initial_ms, max_ms, jitter } => f
+                .debug_struct("Exponential")
+                .field("initial_ms", initial_ms)
+                .field("max_ms", max_ms)
+                .field("jitter", jitter)
+                .finish(),
+            Self::Fixed { delay_ms, jitter } => {
+                f.debug_struct("Fixed").field("delay_ms", delay_ms).field("jitter", jitter).finish()
+            }
+            Self::Fibonacci { initial_ms, max_ms, jitter } => f
+                .debug_struct("Fibonacci")
+                .field("initial_ms", initial_ms)
+                .field("max_ms", max_ms)
+                .field("jitter", jitter)
+                .finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// 1, 1, 2, 3, 5, ... for attempt 0, 1, 2, ..., saturating.
+fn fibonacci(attempt: u32) -> u64 {
+    let (mut a, mut b) = (1u64, 1u64);
+    for _ in 0..attempt {
+        (a, b) = (b, a.saturating_add(b));
+    }
+    a
+}
+
+/// Guards against endpoints that accept connections and drop them right away (a proxy
+/// killing sockets, a host crashing on hello). A connection that ends within
+/// `min_session_ms` of the attempt starting counts as a failed attempt, so it neither
+/// resets the backoff nor the give-up budget. After `max_flaps` of them in a row the breaker
+/// opens and the client waits `cool_off_ms` before trying again.
+#[derive(Clone, Debug)]
+pub struct FlapBreakerConfig {
+    pub min_session_ms: u64,
+    pub max_flaps: u32,
+    pub cool_off_ms: u64,
+}
+
+impl Default for FlapBreakerConfig {
+    fn default() -> Self {
+        Self { min_session_ms: FLAP_MIN_SESSION_MS, max_flaps: FLAP_MAX_FLAPS, cool_off_ms: FLAP_COOL_OFF_MS }
+    }
+}
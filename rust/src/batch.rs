@@ -0,0 +1,49 @@
+use serde_json::{json, Value};
+
+pub const BATCH_MAX_EVENTS: usize = 100;
+pub const BATCH_MAX_BYTES: usize = 64 * 1024;
+
+/// Coalesce queued events into `{type:"batch", events:[...]}` frames. When set, hello
+/// advertises the `batch` capability so the host knows to unwrap them.
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    pub max_events: usize,
+    /// Serialized size of the events in one frame; a single larger event is still sent.
+    pub max_bytes: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self { max_events: BATCH_MAX_EVENTS, max_bytes: BATCH_MAX_BYTES }
+    }
+}
+
+/// Group `events` into batch frames, keeping order. A group of one goes out unwrapped.
+pub(crate) fn frames(events: Vec<Value>, cfg: Option<&BatchConfig>) -> Vec<Value> {
+    let Some(cfg) = cfg else {
+        return events;
+    };
+    let mut out = Vec::new();
+    let mut group: Vec<Value> = Vec::new();
+    let mut bytes = 0;
+    for ev in events {
+        let size = ev.to_string().len();
+        if !group.is_empty() && (group.len() >= cfg.max_events || bytes + size > cfg.max_bytes) {
+            out.push(wrap(std::mem::take(&mut group)));
+            bytes = 0;
+        }
+        bytes += size;
+        group.push(ev);
+    }
+    if !group.is_empty() {
+        out.push(wrap(group));
+    }
+    out
+}
+
+fn wrap(mut group: Vec<Value>) -> Value {
+    if group.len() == 1 {
+        return group.pop().unwrap();
+    }
+    json!({"type":"batch","events":group})
+}
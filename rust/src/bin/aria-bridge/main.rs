@@ -0,0 +1,163 @@
+//! `aria-bridge`: pipe a program's output, or follow its log files, into the bridge.
+//!
+//! ```text
+//! some-service 2>&1 | aria-bridge --level info --project api
+//! aria-bridge tail /var/log/legacy/app.log
+//! ```
+
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use aria_bridge_client::{blocking, BridgeConfig, SendOptions};
+use serde_json::{json, Value};
+
+mod tail;
+
+const USAGE: &str = "\
+usage: aria-bridge [options]
+       aria-bridge [options] tail <path>...
+
+Reads stdin line by line and forwards each line as a console event. With `tail`, follows
+the given files instead (surviving rotation and truncation) until SIGINT or SIGTERM, and
+tags each line's event with its `file`.
+
+options:
+  --url <url>         bridge URL (default: $ARIA_BRIDGE_URL, then ws://localhost:9876)
+  --secret <secret>   auth secret (default: $ARIA_BRIDGE_SECRET, then dev-secret)
+  --project <id>      project id sent in hello
+  --level <level>     console level (default: info)
+  --json              treat lines that are JSON objects with a \"type\" as events and send them as-is
+  -h, --help          show this help";
+
+/// How long to keep trying to deliver, then to close, after stdin ends or tailing stops.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Set by SIGINT or SIGTERM while tailing, which otherwise never ends on its own.
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// Let SIGINT and SIGTERM stop `tail` so buffered lines are flushed before exiting. Not
+/// used for stdin, whose reads retry through the signal and would never notice.
+#[cfg(unix)]
+fn stop_on_signals() {
+    extern "C" fn on_signal(_: libc::c_int) {
+        STOP.store(true, Ordering::SeqCst);
+    }
+    let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+#[cfg(not(unix))]
+fn stop_on_signals() {}
+
+struct Options {
+    cfg: BridgeConfig,
+    level: String,
+    json: bool,
+    /// Files to follow instead of reading stdin.
+    tail: Option<Vec<PathBuf>>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut cfg = BridgeConfig::default();
+    if let Ok(url) = std::env::var("ARIA_BRIDGE_URL") {
+        cfg.url = url;
+    }
+    if let Ok(secret) = std::env::var("ARIA_BRIDGE_SECRET") {
+        cfg.secret = secret;
+    }
+    let mut opts = Options { cfg, level: "info".into(), json: false, tail: None };
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next()).ok_or_else(|| format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--url" => opts.cfg.url = value()?,
+            "--secret" => opts.cfg.secret = value()?,
+            "--project" => opts.cfg.project_id = Some(value()?),
+            "--level" => opts.level = value()?,
+            "--json" => opts.json = true,
+            _ if !flag.starts_with('-') => positional.push(flag),
+            _ => return Err(format!("unknown argument: {flag}")),
+        }
+    }
+    match positional.split_first() {
+        None => {}
+        Some((cmd, paths)) if cmd == "tail" && !paths.is_empty() => opts.tail = Some(paths.iter().map(PathBuf::from).collect()),
+        Some((cmd, _)) if cmd == "tail" => return Err("tail needs at least one path".into()),
+        Some((arg, _)) => return Err(format!("unknown argument: {arg}")),
+    }
+    Ok(opts)
+}
+
+/// With `--json`, an object line carrying a `type` is an event of its own.
+fn passthrough(line: &str) -> Option<Value> {
+    let ev: Value = serde_json::from_str(line).ok()?;
+    ev.get("type")?.as_str()?;
+    Some(ev)
+}
+
+fn forward(bridge: &blocking::BridgeClient, opts: &Options, line: &str, file: Option<&Path>) {
+    match (opts.json.then(|| passthrough(line)).flatten(), file) {
+        (Some(ev), _) => bridge.send_with(ev, SendOptions::default()),
+        (None, Some(file)) => {
+            let ev = json!({"type":"console","level":opts.level,"message":line,"file":file.display().to_string()});
+            bridge.send_with(ev, SendOptions::default());
+        }
+        (None, None) => bridge.send_console(&opts.level, line),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let opts = match parse_args(args.into_iter()) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("aria-bridge: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let tail = opts.tail.as_deref().map(tail::Tail::open);
+    let url = opts.cfg.url.clone();
+    let bridge = match blocking::BridgeClient::new(opts.cfg.clone()) {
+        Ok(bridge) => bridge,
+        Err(e) => {
+            eprintln!("aria-bridge: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(tail) = tail {
+        stop_on_signals();
+        tail.run(&STOP, |file, line| forward(&bridge, &opts, line, Some(file)));
+    } else {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            forward(&bridge, &opts, &line, None);
+        }
+    }
+    let delivered = bridge.flush(SHUTDOWN_TIMEOUT);
+    if !delivered {
+        eprintln!("aria-bridge: could not reach {} to deliver buffered events", url);
+    }
+    match bridge.shutdown(SHUTDOWN_TIMEOUT) {
+        Ok(()) if delivered => ExitCode::SUCCESS,
+        Ok(()) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("aria-bridge: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
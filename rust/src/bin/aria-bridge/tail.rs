@@ -0,0 +1,99 @@
+//! `aria-bridge tail <path>...`: follow log files like `tail -F`. Files are read from their
+//! current end (or from the start if they appear later); when a path is rotated or
+//! truncated, the rest of the old file is read and the new one followed from the top.
+
+use std::fs::{self, File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+struct Follower {
+    path: PathBuf,
+    file: Option<File>,
+    id: Option<(u64, u64)>,
+    pos: u64,
+    partial: Vec<u8>,
+}
+
+impl Follower {
+    fn open(path: PathBuf) -> Self {
+        let mut follower = Self { path, file: None, id: None, pos: 0, partial: Vec::new() };
+        if let (Ok(mut file), Ok(meta)) = (File::open(&follower.path), fs::metadata(&follower.path)) {
+            follower.pos = file.seek(SeekFrom::End(0)).unwrap_or(0);
+            follower.id = file_id(&meta);
+            follower.file = Some(file);
+        }
+        follower
+    }
+
+    fn poll(&mut self, on_line: &mut impl FnMut(&Path, &str)) {
+        let current = fs::metadata(&self.path).ok();
+        let replaced = match (&self.file, &current) {
+            (Some(_), Some(meta)) => file_id(meta) != self.id || meta.len() < self.pos,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        self.read_new(on_line);
+        if replaced {
+            if !self.partial.is_empty() {
+                let rest = std::mem::take(&mut self.partial);
+                on_line(&self.path, String::from_utf8_lossy(&rest).trim_end_matches('\r'));
+            }
+            self.file = File::open(&self.path).ok();
+            self.id = current.as_ref().and_then(file_id);
+            self.pos = 0;
+            self.read_new(on_line);
+        }
+    }
+
+    fn read_new(&mut self, on_line: &mut impl FnMut(&Path, &str)) {
+        let Some(file) = &mut self.file else { return };
+        let mut buf = Vec::new();
+        let Ok(n) = file.read_to_end(&mut buf) else { return };
+        self.pos += n as u64;
+        self.partial.extend_from_slice(&buf);
+        while let Some(end) = self.partial.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            on_line(&self.path, String::from_utf8_lossy(&line[..end]).trim_end_matches('\r'));
+        }
+    }
+}
+
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+/// Without inodes, rotation shows up only as the file shrinking.
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// The files being followed; open them before connecting so no line written after that is missed.
+pub(crate) struct Tail(Vec<Follower>);
+
+impl Tail {
+    pub(crate) fn open(paths: &[PathBuf]) -> Self {
+        Self(paths.iter().cloned().map(Follower::open).collect())
+    }
+
+    /// Poll until `stop` is set, calling `on_line(path, line)` for each complete new line.
+    /// Lines already written when it is set are still read.
+    pub(crate) fn run(mut self, stop: &AtomicBool, mut on_line: impl FnMut(&Path, &str)) {
+        loop {
+            let stopping = stop.load(Ordering::SeqCst);
+            for follower in &mut self.0 {
+                follower.poll(&mut on_line);
+            }
+            if stopping {
+                return;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Synchronous facade for callers without an async runtime (CLI tools, build scripts, the C
+//! and Python bindings): the client's reconnect loop runs on its own thread and
+//! current-thread runtime, and every method returns without awaiting.
+//!
+//! ```no_run
+//! use aria_bridge_client::{blocking, BridgeConfig};
+//!
+//! let bridge = blocking::BridgeClient::new(BridgeConfig::default()).unwrap();
+//! bridge.send_console("info", "build started");
+//! bridge.flush(std::time::Duration::from_secs(2));
+//! bridge.shutdown(std::time::Duration::from_secs(2)).unwrap();
+//! ```
+
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use futures_util::FutureExt;
+use serde_json::Value;
+
+use crate::{BridgeConfig, BridgeError, ControlError, SendOptions, CLOSE_HANDSHAKE_TIMEOUT_MS, CLOSE_NORMAL};
+
+const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Owns a [`crate::BridgeClient`] connected on a background thread. Dropping it without
+/// [`shutdown`](Self::shutdown) requests a close and leaves the thread to finish on its own.
+pub struct BridgeClient {
+    client: crate::BridgeClient,
+    thread: Option<JoinHandle<()>>,
+    done: mpsc::Receiver<()>,
+}
+
+impl BridgeClient {
+    /// Start connecting right away; fails only if the runtime or thread can't be created.
+    pub fn new(cfg: BridgeConfig) -> std::io::Result<Self> {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let client = {
+            let _rt = rt.enter();
+            crate::BridgeClient::new(cfg)
+        };
+        let (done_tx, done) = mpsc::channel();
+        let runner = client.clone();
+        let thread = std::thread::Builder::new().name("aria-bridge".into()).spawn(move || {
+            let _ = rt.block_on(runner.run_with_reconnect());
+            let _ = done_tx.send(());
+        })?;
+        Ok(Self { client, thread: Some(thread), done })
+    }
+
+    /// The underlying async client, for everything else (`stats`, `send_log`, `on_action`...).
+    /// Its async methods need a runtime of their own to await on.
+    pub fn client(&self) -> &crate::BridgeClient {
+        &self.client
+    }
+
+    pub fn send_console(&self, level: &str, message: &str) {
+        self.client.send_console(level, message).now_or_never();
+    }
+
+    pub fn send_error(&self, message: &str) {
+        self.client.send_error(message).now_or_never();
+    }
+
+    /// Any event, as [`crate::BridgeClient::send_with`].
+    pub fn send_with(&self, ev: Value, opts: SendOptions) {
+        self.client.send_with(ev, opts).now_or_never();
+    }
+
+    /// Handlers run on the bridge thread.
+    pub fn on_control<F>(&self, handler: F)
+    where
+        F: Fn(Value) -> Result<Value, ControlError> + Send + Sync + 'static,
+    {
+        self.client.on_control(handler);
+    }
+
+    /// Wait until the client is connected and every event has been written to the socket
+    /// (and acknowledged, with `acks` on), including ones held back by `flush_interval_ms`,
+    /// so a [`shutdown`](Self::shutdown) right after it doesn't leave events behind.
+    /// Returns `false` if that doesn't happen within `deadline`.
+    pub fn flush(&self, deadline: Duration) -> bool {
+        let give_up = Instant::now() + deadline;
+        loop {
+            if self.client.is_flushed() {
+                return true;
+            }
+            if Instant::now() >= give_up {
+                return false;
+            }
+            std::thread::sleep(FLUSH_POLL_INTERVAL);
+        }
+    }
+
+    /// Like [`crate::BridgeClient::shutdown`], blocking the calling thread. On timeout the
+    /// loop is left to finish in the background.
+    #[allow(clippy::result_large_err)]
+    pub fn shutdown(mut self, deadline: Duration) -> Result<(), BridgeError> {
+        self.client.request_close(CLOSE_NORMAL, "shutdown", deadline);
+        let thread = self.thread.take();
+        self.done.recv_timeout(deadline).map_err(|_| BridgeError::ShutdownTimeout)?;
+        if let Some(thread) = thread {
+            let _ = thread.join();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BridgeClient {
+    fn drop(&mut self) {
+        if self.thread.is_some() {
+            self.client.request_close(CLOSE_NORMAL, "shutdown", Duration::from_millis(CLOSE_HANDSHAKE_TIMEOUT_MS));
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Helpers for the application's `build.rs`.
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     aria_bridge_client::build_script::emit_build_info();
+//! }
+//! ```
+//!
+//! Then pass `build_info: Some(aria_bridge_client::build_info!())` in `BridgeConfig`.
+
+use std::process::Command;
+
+/// Export `ARIA_BRIDGE_GIT_SHA` to the crate being built so [`build_info!`](crate::build_info)
+/// can pick it up. Does nothing outside a git checkout.
+pub fn emit_build_info() {
+    let sha = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok());
+    if let Some(sha) = sha {
+        println!("cargo:rustc-env=ARIA_BRIDGE_GIT_SHA={}", sha.trim());
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use crate::protocol::BridgeMessage;
+use crate::{BridgeClient, ControlError};
+
+/// A pluggable capability packaged outside this crate (GPU stats, game-state inspection...).
+///
+/// Its `name` is advertised in hello, `hello_metadata` lands under `metadata.capabilities`,
+/// control requests for any of its `control_actions` are routed to `handle_control`, and if
+/// `interval` is set, `tick` runs on that cadence while connected with its events queued.
+pub trait Capability: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn hello_metadata(&self) -> Option<Value> {
+        None
+    }
+
+    fn control_actions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn handle_control(&self, action: &str, _args: &Value) -> Result<Value, ControlError> {
+        Err(ControlError::not_found(format!("{} does not handle {}", self.name(), action)))
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    fn tick(&self) -> Vec<Value> {
+        Vec::new()
+    }
+}
+
+impl BridgeClient {
+    /// Advertise `name` from now on: sent at once as a `capabilities_update` with the full
+    /// list when connected, and part of every later hello.
+    pub fn add_capability(&self, name: &str) {
+        {
+            let mut caps = self.capabilities.lock().unwrap();
+            if caps.iter().any(|c| c == name) {
+                return;
+            }
+            caps.push(name.to_string());
+        }
+        self.send_capabilities_update();
+    }
+
+    /// Stop advertising `name`, as for [`add_capability`](Self::add_capability). Capabilities
+    /// that follow from the config (`file_transfer`, `batch`...) can't be removed.
+    pub fn remove_capability(&self, name: &str) {
+        {
+            let mut caps = self.capabilities.lock().unwrap();
+            let before = caps.len();
+            caps.retain(|c| c != name);
+            if caps.len() == before {
+                return;
+            }
+        }
+        self.send_capabilities_update();
+    }
+
+    fn send_capabilities_update(&self) {
+        // Disconnected, the next hello carries them.
+        self.send_protocol(BridgeMessage::CapabilitiesUpdate { capabilities: self.hello_capabilities() }.to_json(), false);
+    }
+
+    /// Register a capability; it is included in the next hello.
+    pub fn register_capability<C>(&self, capability: C)
+    where
+        C: Capability + 'static,
+    {
+        self.extensions.lock().unwrap().push(Arc::new(capability));
+    }
+
+    pub(crate) fn extension_metadata(&self) -> Option<Value> {
+        let exts = self.extensions.lock().unwrap();
+        let meta: Map<String, Value> = exts
+            .iter()
+            .filter_map(|c| c.hello_metadata().map(|m| (c.name().to_string(), m)))
+            .collect();
+        (!meta.is_empty()).then_some(Value::Object(meta))
+    }
+
+    pub(crate) fn extension_control(&self, action: &str, args: &Value) -> Option<Result<Value, ControlError>> {
+        let cap = self
+            .extensions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|c| c.control_actions().iter().any(|a| a == action))
+            .cloned()?;
+        Some(cap.handle_control(action, args))
+    }
+
+    pub(crate) fn spawn_extension_tasks(&self) -> Vec<JoinHandle<()>> {
+        let exts = self.extensions.lock().unwrap().clone();
+        exts.into_iter()
+            .filter_map(|cap| {
+                let every = cap.interval()?;
+                let client = self.clone();
+                Some(tokio::spawn(async move {
+                    let mut ticker = time::interval(every);
+                    loop {
+                        ticker.tick().await;
+                        for ev in cap.tick() {
+                            client.enqueue(ev);
+                        }
+                    }
+                }))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::now_ms;
+
+/// File header for wire captures. Each record that follows is
+/// `direction: u8, kind: u8, timestamp_ms: u64 LE, len: u32 LE, payload[len]`.
+pub const CAPTURE_MAGIC: &[u8; 8] = b"ARIACAP1";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// What a captured record holds. Close payloads are the wire form (`u16` BE code, then the
+/// reason); `Error` records hold the read error's message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameKind {
+    Text,
+    Binary,
+    Ping,
+    Pong,
+    Close,
+    Error,
+}
+
+impl FrameKind {
+    fn from_byte(b: u8) -> Option<Self> {
+        Some(match b {
+            1 => FrameKind::Text,
+            2 => FrameKind::Binary,
+            3 => FrameKind::Ping,
+            4 => FrameKind::Pong,
+            5 => FrameKind::Close,
+            6 => FrameKind::Error,
+            _ => return None,
+        })
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            FrameKind::Text => 1,
+            FrameKind::Binary => 2,
+            FrameKind::Ping => 3,
+            FrameKind::Pong => 4,
+            FrameKind::Close => 5,
+            FrameKind::Error => 6,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CaptureRecord {
+    pub direction: Direction,
+    pub kind: FrameKind,
+    pub timestamp_ms: u64,
+    pub payload: Vec<u8>,
+}
+
+/// Iterates the records of a capture written via `BridgeConfig::wire_capture`.
+pub struct CaptureReader<R> {
+    inner: R,
+}
+
+impl CaptureReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        inner.read_exact(&mut magic)?;
+        if &magic != CAPTURE_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an aria-bridge wire capture"));
+        }
+        Ok(Self { inner })
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<CaptureRecord>> {
+        let mut head = [0u8; 14];
+        match self.inner.read_exact(&mut head) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let direction = if head[0] == 0 { Direction::Sent } else { Direction::Received };
+        let kind = FrameKind::from_byte(head[1])
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("unknown frame kind {}", head[1])))?;
+        let timestamp_ms = u64::from_le_bytes(head[2..10].try_into().unwrap());
+        let len = u32::from_le_bytes(head[10..14].try_into().unwrap()) as usize;
+        let mut payload = vec![0u8; len];
+        self.inner.read_exact(&mut payload)?;
+        Ok(Some(CaptureRecord { direction, kind, timestamp_ms, payload }))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = io::Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Writer side; a no-op unless `wire_capture` is configured. Records are flushed as they
+/// are written so a capture survives the process dying mid-session.
+#[derive(Clone, Default)]
+pub(crate) struct WireCapture(Option<Arc<Mutex<BufWriter<File>>>>);
+
+impl WireCapture {
+    pub(crate) fn open(path: Option<&Path>) -> Self {
+        let Some(path) = path else { return Self::default() };
+        let file = OpenOptions::new().create(true).append(true).open(path).and_then(|f| {
+            let mut w = BufWriter::new(f);
+            if w.get_ref().metadata()?.len() == 0 {
+                w.write_all(CAPTURE_MAGIC)?;
+            }
+            Ok(w)
+        });
+        Self(file.ok().map(|w| Arc::new(Mutex::new(w))))
+    }
+
+    pub(crate) fn frame(&self, direction: Direction, msg: &Message) {
+        if self.0.is_none() {
+            return;
+        }
+        let (kind, payload): (FrameKind, Vec<u8>) = match msg {
+            Message::Text(t) => (FrameKind::Text, t.as_bytes().to_vec()),
+            Message::Binary(b) => (FrameKind::Binary, b.to_vec()),
+            Message::Ping(b) => (FrameKind::Ping, b.to_vec()),
+            Message::Pong(b) => (FrameKind::Pong, b.to_vec()),
+            Message::Close(frame) => {
+                let mut out = Vec::new();
+                if let Some(f) = frame {
+                    out.extend_from_slice(&u16::from(f.code).to_be_bytes());
+                    out.extend_from_slice(f.reason.as_bytes());
+                }
+                (FrameKind::Close, out)
+            }
+            Message::Frame(f) => (FrameKind::Binary, f.payload().to_vec()),
+        };
+        self.write(direction, kind, &payload);
+    }
+
+    pub(crate) fn error(&self, err: &impl Display) {
+        if self.0.is_some() {
+            self.write(Direction::Received, FrameKind::Error, err.to_string().as_bytes());
+        }
+    }
+
+    fn write(&self, direction: Direction, kind: FrameKind, payload: &[u8]) {
+        let Some(out) = &self.0 else { return };
+        let mut out = out.lock().unwrap();
+        let mut head = Vec::with_capacity(14 + payload.len());
+        head.push(if direction == Direction::Sent { 0 } else { 1 });
+        head.push(kind.to_byte());
+        head.extend_from_slice(&now_ms().to_le_bytes());
+        head.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        head.extend_from_slice(payload);
+        let _ = out.write_all(&head).and_then(|_| out.flush());
+    }
+}
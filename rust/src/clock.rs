@@ -0,0 +1,80 @@
+use serde_json::Value;
+
+use crate::{now_ms, BridgeClient};
+
+/// Offset between the host's clock and ours, estimated NTP-style from a request we timed
+/// (auth → `auth_success`, ping → pong) and the `serverTime` the host put in its reply.
+#[derive(Default)]
+pub(crate) struct ClockSync {
+    ping_sent: Option<u64>,
+    best_rtt: Option<u64>,
+    offset_ms: Option<i64>,
+    /// The host has answered `time_sync`, so event timestamps move onto its clock.
+    synced: bool,
+}
+
+impl ClockSync {
+    /// Start sampling a new connection; the previous offset stays usable until then.
+    pub(crate) fn reset(&mut self) {
+        self.ping_sent = None;
+        self.best_rtt = None;
+    }
+
+    pub(crate) fn ping_sent(&mut self, at: u64) {
+        self.ping_sent = Some(at);
+    }
+
+    /// Returns the ping's round-trip time.
+    pub(crate) fn pong(&mut self, server_time: Option<u64>) -> Option<u64> {
+        let sent = self.ping_sent.take()?;
+        self.sample(sent, server_time);
+        Some(now_ms().saturating_sub(sent))
+    }
+
+    /// The host's reply to our `time_sync` request sent at `sent`.
+    pub(crate) fn time_sync(&mut self, sent: u64, server_time: u64) {
+        self.sample(sent, Some(server_time));
+        self.synced = true;
+    }
+
+    /// Keep the lowest-latency sample per connection: its midpoint has the smallest error.
+    pub(crate) fn sample(&mut self, sent: u64, server_time: Option<u64>) {
+        let Some(server_time) = server_time else {
+            return;
+        };
+        let received = now_ms();
+        let rtt = received.saturating_sub(sent);
+        if self.best_rtt.is_some_and(|best| best < rtt) {
+            return;
+        }
+        self.best_rtt = Some(rtt);
+        self.offset_ms = Some(server_time as i64 - (sent + rtt / 2) as i64);
+    }
+
+    /// Once time-synced, moves `timestamp` onto the host's clock and keeps the local reading
+    /// as `localTimestamp` (which also marks a replayed event as already moved); with
+    /// `server_timestamps`, adds `serverTimestamp` either way.
+    pub(crate) fn adjust(&self, ev: &mut Value, server_timestamps: bool) {
+        if ev.get("localTimestamp").is_some() {
+            return;
+        }
+        let (Some(offset), Some(ts)) = (self.offset_ms, ev.get("timestamp").and_then(|t| t.as_u64())) else {
+            return;
+        };
+        let host_ts = Value::from((ts as i64 + offset).max(0));
+        if server_timestamps {
+            ev["serverTimestamp"] = host_ts.clone();
+        }
+        if self.synced {
+            ev["localTimestamp"] = ts.into();
+            ev["timestamp"] = host_ts;
+        }
+    }
+}
+
+impl BridgeClient {
+    /// Estimated host clock minus local clock, once a host has reported `serverTime`.
+    pub fn clock_offset_ms(&self) -> Option<i64> {
+        self.clock.lock().unwrap().offset_ms
+    }
+}
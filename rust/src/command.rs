@@ -0,0 +1,86 @@
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+
+use crate::{now_ms, BridgeClient};
+
+/// A [`tokio::process::Command`] whose stdout/stderr lines are forwarded as `console` events
+/// tagged with the child's `pid` and `command` name.
+pub struct BridgeCommand {
+    client: BridgeClient,
+    command: Command,
+    name: String,
+}
+
+impl BridgeCommand {
+    pub fn new(client: &BridgeClient, program: impl AsRef<OsStr>) -> Self {
+        let program = program.as_ref();
+        let name = Path::new(program).file_name().unwrap_or(program).to_string_lossy().into_owned();
+        Self { client: client.clone(), command: Command::new(program), name }
+    }
+
+    /// The `command` tag on forwarded lines; defaults to the program's file name.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.command.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.command.args(args);
+        self
+    }
+
+    /// The wrapped command, for env, working directory, and the rest.
+    pub fn command_mut(&mut self) -> &mut Command {
+        &mut self.command
+    }
+
+    /// Spawn the child with piped stdout/stderr and tail both in background tasks. The
+    /// returned child's `stdout`/`stderr` are taken; wait on it as usual.
+    pub fn spawn(mut self) -> io::Result<Child> {
+        let mut child = self.command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        let pid = child.id();
+        if let Some(out) = child.stdout.take() {
+            tokio::spawn(forward_lines(self.client.clone(), out, "stdout", "info", pid, self.name.clone()));
+        }
+        if let Some(err) = child.stderr.take() {
+            tokio::spawn(forward_lines(self.client, err, "stderr", "warn", pid, self.name));
+        }
+        Ok(child)
+    }
+}
+
+async fn forward_lines<R>(client: BridgeClient, stream: R, name: &str, level: &str, pid: Option<u32>, command: String)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line).await.unwrap_or(0) > 0 {
+        let text = String::from_utf8_lossy(&line);
+        client.enqueue(json!({
+            "type":"console",
+            "level":level,
+            "message":text.trim_end_matches(['\n', '\r']),
+            "stream":name,
+            "pid":pid,
+            "command":command,
+            "timestamp":now_ms()
+        }));
+        line.clear();
+    }
+}
@@ -0,0 +1,44 @@
+use serde_json::Value;
+
+use crate::protocol::AuthSuccess;
+
+pub const COMPRESSION_THRESHOLD_BYTES: usize = 16 * 1024;
+
+/// Compression only applies when the host lists `zstd` in `auth_success.compression`,
+/// so hosts that predate the envelope never receive one.
+pub(crate) fn negotiate(threshold: Option<usize>, auth_success: &AuthSuccess) -> Option<usize> {
+    if !cfg!(feature = "compression") {
+        return None;
+    }
+    let accepted = auth_success.compression.iter().any(|e| e == "zstd");
+    threshold.filter(|_| accepted)
+}
+
+/// Serialize `v`, wrapping it as `{type:"compressed", encoding:"zstd", size, data}` when the
+/// body reaches `threshold` and compression actually shrinks it.
+#[cfg(feature = "compression")]
+pub(crate) fn encode(v: &Value, threshold: Option<usize>) -> String {
+    use base64::engine::general_purpose::STANDARD as B64;
+    use base64::Engine;
+
+    let body = v.to_string();
+    if threshold.is_none_or(|t| body.len() < t) {
+        return body;
+    }
+    match zstd::bulk::compress(body.as_bytes(), zstd::DEFAULT_COMPRESSION_LEVEL) {
+        // base64 adds a third, so only wrap when that still comes out ahead.
+        Ok(packed) if packed.len() * 4 / 3 < body.len() => serde_json::json!({
+            "type": "compressed",
+            "encoding": "zstd",
+            "size": body.len(),
+            "data": B64.encode(packed),
+        })
+        .to_string(),
+        _ => body,
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+pub(crate) fn encode(v: &Value, _threshold: Option<usize>) -> String {
+    v.to_string()
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+use tokio::time;
+
+use crate::protocol::BridgeMessage;
+use crate::{now_ms, BridgeClient, ControlHandler, OutboundSender};
+
+pub const CONTROL_TIMEOUT_MS: u64 = 30_000;
+
+type ControlFuture = BoxFuture<'static, Result<Value, ControlError>>;
+type AsyncControlHandler = Arc<dyn Fn(Value, ControlContext) -> ControlFuture + Send + Sync>;
+
+/// A handler registered with `on_action` or `on_action_async`.
+#[derive(Clone)]
+pub(crate) enum Action {
+    Sync(ControlHandler),
+    Async(AsyncControlHandler),
+}
+
+/// How a control request will be answered.
+pub(crate) enum Dispatch {
+    Ready(Result<Value, ControlError>),
+    /// Runs in the background; the host may `control_cancel` it meanwhile.
+    Pending(ControlFuture),
+}
+
+/// Passed to `on_action_async` handlers.
+#[derive(Clone)]
+pub struct ControlContext {
+    id: String,
+    tx: Option<OutboundSender>,
+}
+
+impl ControlContext {
+    /// The `id` of the `control_request` being handled.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Send a `control_progress {id, progress}` update ahead of the final `control_result`.
+    /// Dropped if the connection has gone away.
+    pub fn progress(&self, progress: Value) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(BridgeMessage::ControlProgress { id: self.id.clone(), progress, timestamp: now_ms() }.to_json());
+        }
+    }
+}
+
+/// A failed control request: `code` is machine-readable, `message` is for people, and `data`
+/// carries any extra detail. Plain strings convert with code `HANDLER_ERROR`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControlError {
+    #[serde(default = "handler_error")]
+    pub code: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+fn handler_error() -> String {
+    ControlError::HANDLER_ERROR.to_string()
+}
+
+impl ControlError {
+    pub const INVALID_ARGS: &'static str = "INVALID_ARGS";
+    pub const NOT_FOUND: &'static str = "NOT_FOUND";
+    pub const CANCELLED: &'static str = "CANCELLED";
+    pub const TIMEOUT: &'static str = "TIMEOUT";
+    pub const HANDLER_ERROR: &'static str = "HANDLER_ERROR";
+
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { code: code.into(), message: message.into(), data: None }
+    }
+
+    pub fn invalid_args(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_ARGS, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(Self::NOT_FOUND, message)
+    }
+
+    pub(crate) fn timed_out(ms: u64) -> Self {
+        Self::new(Self::TIMEOUT, format!("timed out after {}ms", ms))
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl fmt::Display for ControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ControlError {}
+
+impl From<String> for ControlError {
+    fn from(message: String) -> Self {
+        Self::new(Self::HANDLER_ERROR, message)
+    }
+}
+
+impl From<&str> for ControlError {
+    fn from(message: &str) -> Self {
+        Self::new(Self::HANDLER_ERROR, message)
+    }
+}
+
+/// Deserialize a control request's `args` (missing args read as `null`); failures are `INVALID_ARGS`.
+pub fn control_args<A: DeserializeOwned>(msg: &Value) -> Result<A, ControlError> {
+    let args = msg.get("args").cloned().unwrap_or(Value::Null);
+    serde_json::from_value(args).map_err(|e| ControlError::invalid_args(format!("invalid args: {}", e)))
+}
+
+/// Turn a handler's typed outcome into the `Result<Value, ControlError>` an `on_control` handler returns.
+pub fn control_result<R: Serialize, E: Into<ControlError>>(outcome: Result<R, E>) -> Result<Value, ControlError> {
+    let value = outcome.map_err(Into::into)?;
+    serde_json::to_value(value).map_err(|e| format!("unserializable result: {}", e).into())
+}
+
+/// The `control_result` answering request `id`.
+pub(crate) fn control_response(id: &str, outcome: Result<Value, ControlError>) -> Value {
+    BridgeMessage::control_result(id, outcome).to_json()
+}
+
+/// Async control requests still running on the current connection; dropping it aborts them.
+#[derive(Default)]
+pub(crate) struct InFlight {
+    tasks: JoinSet<()>,
+    cancels: HashMap<String, oneshot::Sender<()>>,
+}
+
+impl InFlight {
+    /// Run `fut` in the background and send its `control_result` to `tx`; a cancelled or
+    /// timed-out request gets a `CANCELLED` / `TIMEOUT` error instead.
+    pub(crate) fn start(&mut self, id: &str, fut: ControlFuture, tx: OutboundSender, timeout_ms: Option<u64>) {
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        self.cancels.retain(|_, c| !c.is_closed());
+        self.cancels.insert(id.to_string(), cancel_tx);
+        let id = id.to_string();
+        self.tasks.spawn(async move {
+            let deadline = async {
+                match timeout_ms {
+                    Some(ms) => time::sleep(Duration::from_millis(ms)).await,
+                    None => std::future::pending().await,
+                }
+            };
+            let resp = tokio::select! {
+                outcome = fut => control_response(&id, outcome),
+                _ = deadline => control_response(&id, Err(ControlError::timed_out(timeout_ms.unwrap_or_default()))),
+                Ok(()) = cancel_rx => BridgeMessage::ControlResult {
+                    id,
+                    ok: false,
+                    result: None,
+                    error: Some(ControlError::new(ControlError::CANCELLED, "cancelled")),
+                    cancelled: true,
+                }
+                .to_json(),
+            };
+            let _ = tx.send(resp);
+        });
+    }
+
+    /// Handle `control_cancel {id}`: the handler's future is dropped and the result says `cancelled:true`.
+    pub(crate) fn cancel(&mut self, id: &str) {
+        if let Some(cancel) = self.cancels.remove(id) {
+            let _ = cancel.send(());
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Collect finished tasks; pending while any are still running.
+    pub(crate) async fn reap(&mut self) {
+        self.tasks.join_next().await;
+    }
+}
+
+impl BridgeClient {
+    /// Handle control requests for `action` with typed args and result:
+    /// `client.on_action("resize", |args: Resize| -> Result<Size, ControlError> { ... })`.
+    /// Checked after built-in and capability actions, before the `on_control` fallback.
+    pub fn on_action<A, R, E, F>(&self, action: &str, handler: F)
+    where
+        A: DeserializeOwned,
+        R: Serialize,
+        E: Into<ControlError>,
+        F: Fn(A) -> Result<R, E> + Send + Sync + 'static,
+    {
+        let handler = move |msg: Value| control_result(handler(control_args(&msg)?));
+        self.actions.lock().unwrap().insert(action.to_string(), Action::Sync(Arc::new(handler)));
+    }
+
+    /// Like [`BridgeClient::on_action`] for long-running work: the handler's future runs in the
+    /// background, can report `ctx.progress(..)` along the way, and a `control_cancel {id}`
+    /// from the host drops it and answers with `cancelled: true`.
+    pub fn on_action_async<A, R, E, F, Fut>(&self, action: &str, handler: F)
+    where
+        A: DeserializeOwned,
+        R: Serialize,
+        E: Into<ControlError>,
+        F: Fn(A, ControlContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+    {
+        let handler = move |msg: Value, ctx: ControlContext| -> ControlFuture {
+            match control_args(&msg) {
+                Ok(args) => {
+                    let fut = handler(args, ctx);
+                    Box::pin(async move { control_result(fut.await) })
+                }
+                Err(e) => Box::pin(std::future::ready(Err(e))),
+            }
+        };
+        self.actions.lock().unwrap().insert(action.to_string(), Action::Async(Arc::new(handler)));
+    }
+
+    pub(crate) fn action_control(&self, action: &str, msg: &Value, tx: Option<&OutboundSender>) -> Option<Dispatch> {
+        let handler = self.actions.lock().unwrap().get(action).cloned()?;
+        Some(match handler {
+            Action::Sync(handler) => Dispatch::Ready(handler(msg.clone())),
+            Action::Async(handler) => {
+                let id = msg.get("id").and_then(|i| i.as_str()).unwrap_or_default().to_string();
+                Dispatch::Pending(handler(msg.clone(), ControlContext { id, tx: tx.cloned() }))
+            }
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use serde_json::{json, Value};
+
+use crate::{now_ms, BridgeClient};
+
+pub const CRASH_REPORT_MAX_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Where the application's crash handler (minidumper, crashpad, breakpad...) writes dumps.
+/// Dumps found on the next start are sent as `error` events with an attachment.
+#[derive(Clone, Debug)]
+pub struct CrashReportConfig {
+    pub dir: PathBuf,
+    /// File extension of pending dumps.
+    pub extension: String,
+    pub max_bytes: u64,
+    /// Delete dumps once reported; otherwise they are renamed to `*.reported`.
+    pub delete_reported: bool,
+}
+
+impl CrashReportConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), extension: "dmp".into(), max_bytes: CRASH_REPORT_MAX_BYTES, delete_reported: false }
+    }
+
+    fn pending(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut dumps: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == self.extension.as_str()))
+            .collect();
+        dumps.sort();
+        dumps
+    }
+
+    fn retire(&self, path: &Path) {
+        if self.delete_reported {
+            let _ = fs::remove_file(path);
+        } else {
+            let _ = fs::rename(path, path.with_extension("reported"));
+        }
+    }
+}
+
+/// A queued crash report: the dump and the events (the `error` and its attachment chunks)
+/// not yet delivered.
+struct PendingDump {
+    path: PathBuf,
+    cfg: CrashReportConfig,
+    undelivered: Vec<String>,
+}
+
+/// Dumps are retired only once their whole report has been written (or acknowledged, for
+/// events carrying a `seq`); until then a restart reports them again.
+#[derive(Clone, Default)]
+pub(crate) struct ReportedDumps(Arc<Mutex<Vec<PendingDump>>>);
+
+/// Identifies an event of a crash report: attachment chunks by transfer and index, since
+/// they are stamped out of sight of the report, everything else by `eventId`.
+fn report_key(ev: &Value) -> Option<String> {
+    if ev["type"] == "attachment" {
+        return Some(format!("{}/{}", ev["transferId"].as_str()?, ev["index"].as_u64()?));
+    }
+    ev.get("eventId").and_then(|i| i.as_str()).map(str::to_string)
+}
+
+impl ReportedDumps {
+    /// `frame` (an event or a batch of them) was written to the connection.
+    pub(crate) fn written(&self, frame: &Value) {
+        match frame.get("events").and_then(|e| e.as_array()) {
+            Some(events) if frame["type"] == "batch" => events.iter().for_each(|ev| self.written(ev)),
+            _ if frame.get("seq").is_none() => self.delivered(frame),
+            _ => {}
+        }
+    }
+
+    /// `ev` was written, and acknowledged if it needed to be.
+    pub(crate) fn delivered(&self, ev: &Value) {
+        let mut dumps = self.0.lock().unwrap();
+        if dumps.is_empty() {
+            return;
+        }
+        let Some(key) = report_key(ev) else { return };
+        dumps.iter_mut().for_each(|dump| dump.undelivered.retain(|k| *k != key));
+        dumps.retain(|dump| {
+            if dump.undelivered.is_empty() {
+                dump.cfg.retire(&dump.path);
+            }
+            !dump.undelivered.is_empty()
+        });
+    }
+}
+
+impl BridgeClient {
+    /// Queue any minidumps left behind by a previous crash. Returns how many were found.
+    pub(crate) fn report_pending_crashes(&self, cfg: &CrashReportConfig) -> usize {
+        let dumps = cfg.pending();
+        for path in &dumps {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let meta = fs::metadata(path).ok();
+            let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+            let crashed_at = meta
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+            let attachment = if size <= cfg.max_bytes {
+                fs::read(path).ok().map(|data| self.stream_attachment(&name, "application/x-dmp", &data))
+            } else {
+                None
+            };
+            let mut ev = json!({
+                "type":"error",
+                "level":"error",
+                "message":format!("native crash in previous run ({})", name),
+                "crash":{"file":name,"size":size,"crashedAt":crashed_at,"truncated":attachment.is_none()},
+                "attachments":attachment.into_iter().collect::<Vec<_>>(),
+                "debug":crate::debug_meta::debug_meta(&self.cfg),
+                "timestamp":now_ms()
+            });
+            self.stamp(&mut ev);
+            let mut undelivered: Vec<String> = report_key(&ev).into_iter().collect();
+            for att in ev["attachments"].as_array().into_iter().flatten() {
+                let chunks = att["chunks"].as_u64().unwrap_or(0);
+                undelivered.extend((0..chunks).map(|i| format!("{}/{}", att["transferId"].as_str().unwrap_or_default(), i)));
+            }
+            let dump = PendingDump { path: path.clone(), cfg: cfg.clone(), undelivered };
+            self.reported_dumps.0.lock().unwrap().push(dump);
+            self.enqueue(ev);
+        }
+        dumps.len()
+    }
+}
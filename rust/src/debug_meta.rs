@@ -0,0 +1,102 @@
+use std::sync::OnceLock;
+
+use serde_json::{json, Value};
+
+use crate::BridgeConfig;
+
+/// Identity of the running binary attached to error events so hosts can pick the right
+/// symbols / source revision instead of showing bare addresses. `crateVersion` is this
+/// client's version; the application's own name and version come from `build_info`.
+pub(crate) fn debug_meta(cfg: &BridgeConfig) -> Value {
+    static EXECUTABLE: OnceLock<Option<String>> = OnceLock::new();
+    static BUILD_ID: OnceLock<Option<String>> = OnceLock::new();
+    let executable = EXECUTABLE.get_or_init(|| {
+        std::env::current_exe().ok().and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+    });
+    let build_id = BUILD_ID.get_or_init(read_build_id);
+    let mut meta = json!({
+        "executable": executable,
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "buildId": build_id,
+        "crateVersion": env!("CARGO_PKG_VERSION"),
+    });
+    if let Some(build) = &cfg.build_info {
+        meta["app"] = json!(build.name);
+        meta["appVersion"] = json!(build.version);
+        if let Some(sha) = &build.git_sha {
+            meta["gitSha"] = json!(sha);
+        }
+    }
+    meta
+}
+
+/// GNU build-id from the executable's `.note.gnu.build-id` (64-bit little-endian ELF only).
+#[cfg(target_os = "linux")]
+fn read_build_id() -> Option<String> {
+    use std::fs::File;
+    use std::io::{Read, Seek, SeekFrom};
+
+    const SHT_NOTE: u32 = 7;
+    const NT_GNU_BUILD_ID: u32 = 3;
+
+    fn u16_at(b: &[u8], off: usize) -> usize {
+        u16::from_le_bytes([b[off], b[off + 1]]) as usize
+    }
+    fn u32_at(b: &[u8], off: usize) -> u32 {
+        u32::from_le_bytes(b[off..off + 4].try_into().unwrap())
+    }
+    fn u64_at(b: &[u8], off: usize) -> u64 {
+        u64::from_le_bytes(b[off..off + 8].try_into().unwrap())
+    }
+
+    let mut file = File::open("/proc/self/exe").ok()?;
+    let mut header = [0u8; 64];
+    file.read_exact(&mut header).ok()?;
+    // ELF magic, 64-bit class, little-endian.
+    if &header[..4] != b"\x7fELF" || header[4] != 2 || header[5] != 1 {
+        return None;
+    }
+    let sh_off = u64_at(&header, 0x28);
+    let sh_entsize = u16_at(&header, 0x3A);
+    let sh_num = u16_at(&header, 0x3C);
+    if sh_entsize < 64 || sh_num == 0 {
+        return None;
+    }
+    let mut sections = vec![0u8; sh_entsize * sh_num];
+    file.seek(SeekFrom::Start(sh_off)).ok()?;
+    file.read_exact(&mut sections).ok()?;
+    for sh in sections.chunks_exact(sh_entsize) {
+        if u32_at(sh, 4) != SHT_NOTE {
+            continue;
+        }
+        let (off, size) = (u64_at(sh, 0x18), u64_at(sh, 0x20) as usize);
+        if size > 4096 {
+            continue;
+        }
+        let mut notes = vec![0u8; size];
+        file.seek(SeekFrom::Start(off)).ok()?;
+        file.read_exact(&mut notes).ok()?;
+        let mut pos = 0;
+        while pos + 12 <= notes.len() {
+            let namesz = u32_at(&notes, pos) as usize;
+            let descsz = u32_at(&notes, pos + 4) as usize;
+            let kind = u32_at(&notes, pos + 8);
+            let desc_start = pos + 12 + namesz.div_ceil(4) * 4;
+            let desc_end = desc_start + descsz;
+            if desc_end > notes.len() {
+                break;
+            }
+            if kind == NT_GNU_BUILD_ID && &notes[pos + 12..pos + 12 + namesz] == b"GNU\0" {
+                return Some(notes[desc_start..desc_end].iter().map(|b| format!("{:02x}", b)).collect());
+            }
+            pos = desc_end.div_ceil(4) * 4;
+        }
+    }
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_build_id() -> Option<String> {
+    None
+}
@@ -0,0 +1,53 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+
+use crate::now_ms;
+use crate::stats::{count_drop, DropCounts};
+
+pub const EARLY_BUFFER_LIMIT: usize = 64;
+
+struct EarlyBuffer {
+    events: VecDeque<Value>,
+    dropped: DropCounts,
+}
+
+static EARLY: Mutex<EarlyBuffer> = Mutex::new(EarlyBuffer { events: VecDeque::new(), dropped: BTreeMap::new() });
+
+/// Record an event before any [`BridgeClient`](crate::BridgeClient) exists (config loading,
+/// argument parsing). The first client created picks these up. Keeps the newest
+/// [`EARLY_BUFFER_LIMIT`] events; prefer the [`early_log!`] / [`early_error!`] macros.
+pub fn early_event(level: &str, message: &str) {
+    let ty = if level == "error" { "error" } else { "console" };
+    let ev = json!({"type":ty,"level":level,"message":message,"early":true,"timestamp":now_ms()});
+    let mut early = EARLY.lock().unwrap();
+    if early.events.len() >= EARLY_BUFFER_LIMIT {
+        if let Some(old) = early.events.pop_front() {
+            count_drop(&mut early.dropped, &old);
+        }
+    }
+    early.events.push_back(ev);
+}
+
+/// Everything recorded so far, plus how many of each type were evicted.
+pub(crate) fn take() -> (Vec<Value>, DropCounts) {
+    let mut early = EARLY.lock().unwrap();
+    (early.events.drain(..).collect(), std::mem::take(&mut early.dropped))
+}
+
+/// `early_log!("info", "loading {}", path)`: a console event recorded before the client exists.
+#[macro_export]
+macro_rules! early_log {
+    ($level:expr, $($arg:tt)+) => {
+        $crate::early_event($level, &format!($($arg)+))
+    };
+}
+
+/// `early_error!("bad config: {}", err)`: an error event recorded before the client exists.
+#[macro_export]
+macro_rules! early_error {
+    ($($arg:tt)+) => {
+        $crate::early_event("error", &format!($($arg)+))
+    };
+}
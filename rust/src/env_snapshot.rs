@@ -0,0 +1,93 @@
+use serde_json::{json, Map, Value};
+use url::Url;
+
+use crate::BridgeConfig;
+
+pub const REDACTED: &str = "[redacted]";
+pub const DEFAULT_REDACT_KEYS: &[&str] = &["SECRET", "TOKEN", "PASSWORD", "PASSWD", "KEY", "AUTH", "CREDENTIAL", "COOKIE"];
+
+/// Settings for the built-in `get_env` control action.
+#[derive(Clone, Debug)]
+pub struct EnvSnapshotConfig {
+    /// Only variables starting with one of these prefixes are reported (empty = all).
+    pub include_prefixes: Vec<String>,
+    /// Case-insensitive substrings; matching variable names have their values replaced.
+    pub redact_keys: Vec<String>,
+}
+
+impl Default for EnvSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            include_prefixes: Vec::new(),
+            redact_keys: DEFAULT_REDACT_KEYS.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+}
+
+impl EnvSnapshotConfig {
+    pub fn should_redact(&self, key: &str) -> bool {
+        let upper = key.to_ascii_uppercase();
+        self.redact_keys.iter().any(|k| upper.contains(&k.to_ascii_uppercase()))
+    }
+
+    fn includes(&self, key: &str) -> bool {
+        self.include_prefixes.is_empty() || self.include_prefixes.iter().any(|p| key.starts_with(p.as_str()))
+    }
+
+    /// `get_env` → `{env, config, cwd}` with sensitive values redacted; `url` is the current
+    /// endpoint, which may differ from `cfg.url` after a redirect or failover.
+    pub(crate) fn snapshot(&self, cfg: &BridgeConfig, url: &str) -> Value {
+        let mut vars: Vec<(String, String)> = std::env::vars_os()
+            .map(|(k, v)| (k.to_string_lossy().into_owned(), v.to_string_lossy().into_owned()))
+            .filter(|(k, _)| self.includes(k))
+            .collect();
+        vars.sort();
+        let env: Map<String, Value> = vars
+            .into_iter()
+            .map(|(k, v)| {
+                let v = if self.should_redact(&k) { REDACTED.to_string() } else { v };
+                (k, Value::String(v))
+            })
+            .collect();
+        json!({
+            "env": env,
+            "config": {
+                "url": redact_url(url),
+                "secret": REDACTED,
+                "projectId": cfg.project_id,
+                "capabilities": cfg.capabilities,
+                "heartbeatIntervalMs": cfg.heartbeat_interval_ms,
+                "heartbeatTimeoutMs": cfg.heartbeat_timeout_ms,
+                "backoffInitialMs": cfg.backoff_initial_ms,
+                "backoffMaxMs": cfg.backoff_max_ms,
+                "bufferLimit": cfg.buffer_limit,
+            },
+            "cwd": std::env::current_dir().ok().map(|p| p.display().to_string()),
+        })
+    }
+}
+
+/// `url` without userinfo and with every query value replaced by [`REDACTED`], since either
+/// may carry credentials. Unparseable input is redacted whole.
+pub(crate) fn redact_url(url: &str) -> String {
+    let Ok(mut url) = Url::parse(url) else {
+        return REDACTED.to_string();
+    };
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    if url.query().is_some() {
+        let query: Vec<String> = url.query_pairs().map(|(k, _)| format!("{}={}", k, REDACTED)).collect();
+        url.set_query(Some(&query.join("&")));
+    }
+    url.to_string()
+}
+
+/// `url` without userinfo. Unparseable input is redacted whole.
+pub(crate) fn strip_userinfo(url: &str) -> String {
+    let Ok(mut url) = Url::parse(url) else {
+        return REDACTED.to_string();
+    };
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.to_string()
+}
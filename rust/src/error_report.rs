@@ -0,0 +1,55 @@
+use std::backtrace::Backtrace;
+use std::error::Error;
+
+use serde_json::{json, Value};
+
+use crate::{debug_meta, now_ms, BridgeClient};
+
+impl BridgeClient {
+    /// Send `err` as an `error` event with its `source()` chain as `causes` and the current
+    /// stack as `frames` (`{function, file?, line?, column?}`, innermost first). Captures a
+    /// backtrace regardless of `RUST_BACKTRACE`.
+    pub async fn send_error_with_backtrace(&self, err: &dyn Error) {
+        let mut causes = Vec::new();
+        let mut source = err.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        let ev = json!({
+            "type":"error",
+            "message":err.to_string(),
+            "causes":causes,
+            "frames":parse_frames(&Backtrace::force_capture().to_string()),
+            "timestamp":now_ms(),
+            "debug":debug_meta::debug_meta(&self.cfg)
+        });
+        self.enqueue(ev);
+    }
+}
+
+/// Parse `Backtrace`'s display form (`N: symbol` lines, each optionally followed by
+/// `at file:line:col`) into frame objects; the frame API itself isn't stable.
+fn parse_frames(rendered: &str) -> Vec<Value> {
+    let mut frames: Vec<Value> = Vec::new();
+    for line in rendered.lines().map(str::trim) {
+        if let Some(at) = line.strip_prefix("at ") {
+            let Some(frame) = frames.last_mut() else { continue };
+            let mut parts = at.rsplitn(3, ':');
+            let (column, line, file) = (parts.next(), parts.next(), parts.next());
+            match (file, line.and_then(|l| l.parse::<u64>().ok()), column.and_then(|c| c.parse::<u64>().ok())) {
+                (Some(file), Some(line), Some(column)) => {
+                    frame["file"] = file.into();
+                    frame["line"] = line.into();
+                    frame["column"] = column.into();
+                }
+                _ => frame["file"] = at.into(),
+            }
+        } else if let Some((index, function)) = line.split_once(": ") {
+            if index.chars().all(|c| c.is_ascii_digit()) {
+                frames.push(json!({"function": function}));
+            }
+        }
+    }
+    frames
+}
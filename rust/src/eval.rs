@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::time;
+
+use crate::control::Dispatch;
+use crate::{BridgeClient, ControlError};
+
+pub const EVAL_TIMEOUT_MS: u64 = 5_000;
+
+/// Limits for the opt-in `eval` control action. Unless `allowlist` has a `"*"` entry (which
+/// allows any code), only single commands are evaluated: an allowlisted command name followed
+/// by plain-word arguments (letters, digits and `_ . , : / = + - @ %`). Anything else, such as
+/// quoting, `;`, pipes, brackets or substitutions, is refused before the evaluator sees it.
+#[derive(Clone, Debug)]
+pub struct EvalConfig {
+    pub allowlist: Vec<String>,
+    /// Past this the request fails with `TIMEOUT`, but the evaluation is not cancelled: it keeps
+    /// its blocking-pool thread until it returns. An evaluator that can run unbounded should
+    /// enforce its own step or time limit.
+    pub timeout_ms: u64,
+}
+
+impl Default for EvalConfig {
+    fn default() -> Self {
+        Self { allowlist: Vec::new(), timeout_ms: EVAL_TIMEOUT_MS }
+    }
+}
+
+impl EvalConfig {
+    fn allows(&self, code: &str) -> bool {
+        if self.allowlist.iter().any(|entry| entry == "*") {
+            return true;
+        }
+        let mut words = code.split_whitespace();
+        let Some(command) = words.next() else { return false };
+        self.allowlist.iter().any(|entry| entry == command) && words.all(is_plain_word)
+    }
+}
+
+fn is_plain_word(word: &str) -> bool {
+    word.chars().all(|c| c.is_alphanumeric() || "_.,:/=+-@%".contains(c))
+}
+
+/// Application-supplied interpreter or command dispatcher behind the `eval` action.
+pub trait Evaluator: Send + Sync {
+    fn eval(&self, code: &str) -> Result<Value, String>;
+}
+
+impl<F> Evaluator for F
+where
+    F: Fn(&str) -> Result<Value, String> + Send + Sync,
+{
+    fn eval(&self, code: &str) -> Result<Value, String> {
+        self(code)
+    }
+}
+
+pub(crate) type EvalSlot = Option<(EvalConfig, Arc<dyn Evaluator>)>;
+
+impl BridgeClient {
+    /// `eval {code}` (or a top-level `code` field, as the JS host sends it). The evaluator
+    /// runs on the blocking pool, answered like an `on_action_async` handler, so a slow
+    /// expression never holds up the connection; past `timeout_ms` the request fails with
+    /// `TIMEOUT` and the evaluation is left to finish in the background.
+    pub(crate) fn run_eval(&self, msg: &Value) -> Option<Dispatch> {
+        let (cfg, evaluator) = self.evaluator.lock().unwrap().clone()?;
+        let code = msg
+            .get("args")
+            .and_then(|a| a.get("code"))
+            .or_else(|| msg.get("code"))
+            .and_then(|c| c.as_str())
+            .map(str::to_string);
+        let Some(code) = code else {
+            return Some(Dispatch::Ready(Err("missing args.code".into())));
+        };
+        if !cfg.allows(&code) {
+            return Some(Dispatch::Ready(Err(format!("eval not allowed: {}", code).into())));
+        }
+        Some(Dispatch::Pending(Box::pin(async move {
+            let task = tokio::task::spawn_blocking(move || evaluator.eval(&code));
+            match time::timeout(Duration::from_millis(cfg.timeout_ms), task).await {
+                Ok(Ok(outcome)) => outcome.map_err(ControlError::from),
+                Ok(Err(e)) => Err(format!("eval failed: {}", e).into()),
+                Err(_) => Err(ControlError::timed_out(cfg.timeout_ms)),
+            }
+        })))
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use tokio::time;
+use url::Url;
+
+use crate::BridgeClient;
+
+pub const FAILBACK_PROBE_MS: u64 = 30_000;
+
+impl BridgeClient {
+    /// `BridgeConfig::urls` in priority order, or just `url` when none are listed.
+    pub(crate) fn endpoints(&self) -> Vec<String> {
+        if self.cfg.urls.is_empty() {
+            vec![self.cfg.url.clone()]
+        } else {
+            self.cfg.urls.clone()
+        }
+    }
+
+    /// After a failed attempt, move on to the endpoint after the current one (the primary
+    /// if the current one isn't in the list, e.g. after a redirect).
+    pub(crate) fn rotate_endpoint(&self) {
+        let endpoints = self.endpoints();
+        if endpoints.len() < 2 {
+            return;
+        }
+        let mut url = self.url.lock().unwrap();
+        let next = endpoints.iter().position(|u| *u == *url).map_or(0, |i| (i + 1) % endpoints.len());
+        *url = endpoints[next].clone();
+    }
+
+    /// While connected to a standby, try the primary every `failback_probe_ms`; completes
+    /// once it accepts a connection, with the primary as the new target. Never completes
+    /// while already on the primary.
+    pub(crate) async fn probe_primary(&self) {
+        let endpoints = self.endpoints();
+        let primary = &endpoints[0];
+        if endpoints.len() < 2 || self.url() == *primary {
+            return std::future::pending().await;
+        }
+        let interval = Duration::from_millis(self.cfg.failback_probe_ms);
+        loop {
+            time::sleep(interval).await;
+            if let Ok(Ok(probe)) = time::timeout(interval, self.open_connection_to(primary)).await {
+                drop(probe);
+                *self.url.lock().unwrap() = primary.clone();
+                return;
+            }
+        }
+    }
+
+    /// Whether a host `redirect` to `target` may be followed (see
+    /// `BridgeConfig::redirect_hosts`): the secret goes out in the next `auth`, so it must
+    /// not end up in plaintext or somewhere unexpected.
+    pub(crate) fn redirect_allowed(&self, target: &str) -> bool {
+        let (Ok(target), Ok(current)) = (Url::parse(target), Url::parse(&self.url())) else {
+            return false;
+        };
+        if !matches!(target.scheme(), "ws" | "wss" | "tcp") {
+            return false;
+        }
+        if current.scheme() == "wss" && target.scheme() != "wss" {
+            return false;
+        }
+        let same_place = |other: &Url| {
+            other.host_str().zip(target.host_str()).is_some_and(|(a, b)| a.eq_ignore_ascii_case(b))
+                && other.port_or_known_default() == target.port_or_known_default()
+        };
+        if self.cfg.redirect_hosts.is_empty() {
+            return same_place(&current);
+        }
+        // Entries read as `host[:port]` under the target's scheme, so a bare host means its default port.
+        self.cfg.redirect_hosts.iter().filter_map(|allowed| Url::parse(&format!("{}://{}", target.scheme(), allowed)).ok()).any(|allowed| same_place(&allowed))
+    }
+}
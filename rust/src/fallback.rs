@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::rotating_file::RotatingFile;
+use crate::{stats, BridgeClient};
+
+pub const FALLBACK_THRESHOLD_MS: u64 = 60_000;
+pub const FALLBACK_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+pub const FALLBACK_MAX_FILES: usize = 5;
+
+/// Once the bridge has been disconnected for `threshold_ms`, new events are appended to a
+/// rotating JSONL file at `path` instead of the in-memory buffer, so nothing is lost when
+/// the buffer would wrap. With `spill_dir` set, events evicted from a full buffer are
+/// written to rotating segment files there (`overflow.jsonl`, `overflow.1.jsonl`...)
+/// instead of being dropped. Both use the `max_file_bytes`/`max_files` limits, and events
+/// in a segment that rotation deletes are counted as dropped. With `upload_on_reconnect`,
+/// the files are replayed (offline events, then overflow) and removed after the next
+/// successful hello; lines that don't parse (a write cut short by a crash) are skipped.
+#[derive(Clone, Debug)]
+pub struct FallbackConfig {
+    pub path: PathBuf,
+    pub threshold_ms: u64,
+    pub max_file_bytes: u64,
+    pub max_files: usize,
+    pub spill_dir: Option<PathBuf>,
+    pub upload_on_reconnect: bool,
+}
+
+impl FallbackConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            threshold_ms: FALLBACK_THRESHOLD_MS,
+            max_file_bytes: FALLBACK_MAX_FILE_BYTES,
+            max_files: FALLBACK_MAX_FILES,
+            spill_dir: None,
+            upload_on_reconnect: true,
+        }
+    }
+}
+
+pub(crate) struct FallbackState {
+    pub(crate) disconnected_since: Option<Instant>,
+    file: Option<RotatingFile>,
+    spill: Option<RotatingFile>,
+}
+
+impl FallbackState {
+    pub(crate) fn new(cfg: Option<&FallbackConfig>) -> Arc<Mutex<Self>> {
+        let rotating = |c: &FallbackConfig, path: PathBuf| RotatingFile::new(path, c.max_file_bytes, c.max_files).tracking_discarded();
+        Arc::new(Mutex::new(Self {
+            disconnected_since: Some(Instant::now()),
+            file: cfg.map(|c| rotating(c, c.path.clone())),
+            spill: cfg.and_then(|c| Some(rotating(c, c.spill_dir.as_ref()?.join("overflow.jsonl")))),
+        }))
+    }
+}
+
+impl BridgeClient {
+    /// Write `ev` to the fallback file if we've been offline past the threshold.
+    /// Returns false when the event should go to the in-memory buffer as usual.
+    pub(crate) fn spill_to_fallback(&self, ev: &Value) -> bool {
+        let Some(cfg) = &self.cfg.fallback else { return false };
+        let mut state = self.fallback.lock().unwrap();
+        let offline_long = state
+            .disconnected_since
+            .is_some_and(|since| since.elapsed() >= Duration::from_millis(cfg.threshold_ms));
+        let (written, discarded) = match (&mut state.file, offline_long) {
+            (Some(file), true) => (file.append_line(&ev.to_string()).is_ok(), file.take_discarded()),
+            _ => return false,
+        };
+        drop(state);
+        self.count_discarded(discarded);
+        written
+    }
+
+    /// Write an event evicted from the full buffer to the spill segments, when enabled.
+    /// Returns false when it should be counted as dropped.
+    pub(crate) fn spill_overflow(&self, ev: &Value) -> bool {
+        let mut state = self.fallback.lock().unwrap();
+        let Some(spill) = state.spill.as_mut() else { return false };
+        let (written, discarded) = (spill.append_line(&ev.to_string()).is_ok(), spill.take_discarded());
+        drop(state);
+        self.count_discarded(discarded);
+        written
+    }
+
+    /// Events in files that rotation deleted are gone; report them like evicted ones.
+    fn count_discarded(&self, lines: Vec<String>) {
+        if lines.is_empty() {
+            return;
+        }
+        let mut dropped = self.dropped.lock().unwrap();
+        let mut stats = self.stats.lock().unwrap();
+        for ev in lines.iter().filter_map(|l| serde_json::from_str::<Value>(l).ok()) {
+            stats::count_drop(&mut dropped, &ev);
+            stats.dropped(&ev);
+        }
+    }
+
+    pub(crate) fn set_connected(&self, connected: bool) {
+        self.stats.lock().unwrap().set_connected(connected);
+        let mut state = self.fallback.lock().unwrap();
+        if connected {
+            state.disconnected_since = None;
+        } else if state.disconnected_since.is_none() {
+            state.disconnected_since = Some(Instant::now());
+        }
+    }
+
+    /// Events spilled while offline, oldest first; empties the fallback files.
+    pub(crate) fn take_fallback_events(&self) -> Vec<Value> {
+        if !self.cfg.fallback.as_ref().is_some_and(|c| c.upload_on_reconnect) {
+            return Vec::new();
+        }
+        let mut state = self.fallback.lock().unwrap();
+        let state = &mut *state;
+        let lines = state.file.iter_mut().chain(state.spill.iter_mut()).flat_map(|file| file.drain_lines());
+        lines.filter_map(|l| serde_json::from_str(&l).ok()).collect()
+    }
+}
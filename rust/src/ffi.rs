@@ -0,0 +1,136 @@
+//! C ABI (feature `ffi`), declared in `include/aria_bridge.h`. Each [`AriaBridge`] handle wraps
+//! a [`blocking::BridgeClient`], so the host program needs no async runtime. Strings are NUL-terminated UTF-8; functions returning `int` give 0 on success
+//! and -1 on a null handle, invalid string, or timeout.
+
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::blocking;
+use crate::{BridgeConfig, ControlError};
+
+/// Opaque client handle returned by [`aria_bridge_new`].
+pub struct AriaBridge(blocking::BridgeClient);
+
+/// Handles a control request given as JSON (`{type, id, action, args, ...}`) and returns the
+/// result as JSON (a bare string is sent as a JSON string), or NULL to fail the request.
+/// The returned string is copied before the callback's next call and must stay valid until then.
+pub type AriaControlCallback =
+    Option<unsafe extern "C" fn(request_json: *const c_char, user_data: *mut c_void) -> *const c_char>;
+
+/// The caller's `user_data`, handed back to the callback on the client's thread.
+struct UserData(*mut c_void);
+
+// SAFETY: the pointer is only passed back to the caller's callback, which the caller
+// promises is safe to invoke from the bridge thread.
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+impl UserData {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok()
+}
+
+/// Connect to `url` with `secret`, tagging the session with `project_id` (may be NULL).
+/// Returns NULL if an argument is not valid UTF-8 or the runtime can't start. Release the
+/// handle with [`aria_bridge_shutdown`].
+///
+/// # Safety
+/// `url` and `secret` must be valid NUL-terminated strings; `project_id` must be one or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn aria_bridge_new(
+    url: *const c_char,
+    secret: *const c_char,
+    project_id: *const c_char,
+) -> *mut AriaBridge {
+    let (Some(url), Some(secret)) = (str_arg(url), str_arg(secret)) else { return std::ptr::null_mut() };
+    if !project_id.is_null() && str_arg(project_id).is_none() {
+        return std::ptr::null_mut();
+    }
+    let project_id = str_arg(project_id).map(str::to_string);
+    let cfg = BridgeConfig { url: url.to_string(), secret: secret.to_string(), project_id, ..Default::default() };
+    match blocking::BridgeClient::new(cfg) {
+        Ok(bridge) => Box::into_raw(Box::new(AriaBridge(bridge))),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Enqueue a `console` event.
+///
+/// # Safety
+/// `bridge` must come from [`aria_bridge_new`] and not be shut down; `level` and `message`
+/// must be valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn aria_bridge_send_console(
+    bridge: *mut AriaBridge,
+    level: *const c_char,
+    message: *const c_char,
+) -> c_int {
+    let (Some(bridge), Some(level), Some(message)) = (bridge.as_ref(), str_arg(level), str_arg(message)) else {
+        return -1;
+    };
+    bridge.0.send_console(level, message);
+    0
+}
+
+/// Enqueue an `error` event.
+///
+/// # Safety
+/// As for [`aria_bridge_send_console`].
+#[no_mangle]
+pub unsafe extern "C" fn aria_bridge_send_error(bridge: *mut AriaBridge, message: *const c_char) -> c_int {
+    let (Some(bridge), Some(message)) = (bridge.as_ref(), str_arg(message)) else { return -1 };
+    bridge.0.send_error(message);
+    0
+}
+
+/// Answer control requests with `callback`, which runs on the bridge thread and gets
+/// `user_data` back unchanged. Replaces any earlier callback.
+///
+/// # Safety
+/// `bridge` must be a live handle; `callback` must be safe to call from another thread with
+/// `user_data` until the bridge is shut down.
+#[no_mangle]
+pub unsafe extern "C" fn aria_bridge_on_control(
+    bridge: *mut AriaBridge,
+    callback: AriaControlCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    let (Some(bridge), Some(callback)) = (bridge.as_ref(), callback) else { return -1 };
+    let user_data = UserData(user_data);
+    bridge.0.on_control(move |msg| {
+        let request = CString::new(msg.to_string()).map_err(|e| ControlError::from(e.to_string()))?;
+        let out = unsafe { callback(request.as_ptr(), user_data.get()) };
+        if out.is_null() {
+            return Err(ControlError::from("control callback failed"));
+        }
+        let text = unsafe { CStr::from_ptr(out) }.to_string_lossy();
+        Ok(serde_json::from_str(&text).unwrap_or_else(|_| Value::String(text.into_owned())))
+    });
+    0
+}
+
+/// Flush, say goodbye, close, and free the handle, waiting up to `timeout_ms`. On timeout
+/// (-1) the handle is still freed and the bridge thread finishes in the background.
+///
+/// # Safety
+/// `bridge` must come from [`aria_bridge_new`]; it is invalid after this call.
+#[no_mangle]
+pub unsafe extern "C" fn aria_bridge_shutdown(bridge: *mut AriaBridge, timeout_ms: u64) -> c_int {
+    if bridge.is_null() {
+        return -1;
+    }
+    match Box::from_raw(bridge).0.shutdown(Duration::from_millis(timeout_ms)) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::ControlError;
+
+pub const FILE_TRANSFER_CHUNK_BYTES: usize = 64 * 1024;
+pub const FILE_TRANSFER_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Opt-in settings for the built-in `read_file` / `write_file` control actions.
+/// Paths outside `allowed_roots` are rejected; relative paths resolve against the first root.
+#[derive(Clone, Debug)]
+pub struct FileTransferConfig {
+    pub allowed_roots: Vec<PathBuf>,
+    pub allow_write: bool,
+    pub max_file_bytes: u64,
+    pub chunk_bytes: usize,
+}
+
+impl Default for FileTransferConfig {
+    fn default() -> Self {
+        Self {
+            allowed_roots: Vec::new(),
+            allow_write: false,
+            max_file_bytes: FILE_TRANSFER_MAX_BYTES,
+            chunk_bytes: FILE_TRANSFER_CHUNK_BYTES,
+        }
+    }
+}
+
+impl FileTransferConfig {
+    fn within_roots(&self, path: &Path) -> bool {
+        self.allowed_roots.iter().filter_map(|r| fs::canonicalize(r).ok()).any(|r| path.starts_with(r))
+    }
+
+    fn resolve(&self, raw: &str) -> Result<PathBuf, ControlError> {
+        let root = self.allowed_roots.first().ok_or("file transfer has no allowed roots")?;
+        let joined = root.join(raw);
+        // Canonicalize the parent so not-yet-written files can still be checked.
+        let (dir, name) = match (joined.parent(), joined.file_name()) {
+            (Some(dir), Some(name)) => (dir, name),
+            _ => return Err(ControlError::invalid_args(format!("invalid path: {}", raw))),
+        };
+        let dir = fs::canonicalize(dir).map_err(|e| io_error(raw, e))?;
+        let resolved = dir.join(name);
+        // An existing symlink inside a root could still point elsewhere.
+        let target = fs::canonicalize(&resolved).unwrap_or_else(|_| resolved.clone());
+        if !self.within_roots(&dir) || !self.within_roots(&target) {
+            return Err(ControlError::invalid_args(format!("path not allowed: {}", raw)));
+        }
+        Ok(resolved)
+    }
+
+    /// `read_file {path, offset?}` → one chunk of at most `chunk_bytes`, base64-encoded.
+    /// Hosts keep requesting with the next offset until `eof` is true.
+    pub(crate) fn read_file(&self, args: &Value) -> Result<Value, ControlError> {
+        let raw = path_arg(args)?;
+        let offset = args.get("offset").and_then(|o| o.as_u64()).unwrap_or(0);
+        let path = self.resolve(raw)?;
+        let mut file = fs::File::open(&path).map_err(|e| io_error(raw, e))?;
+        let size = file.metadata().map_err(|e| e.to_string())?.len();
+        if size > self.max_file_bytes {
+            return Err(ControlError::invalid_args(format!("file too large: {} bytes (max {})", size, self.max_file_bytes)));
+        }
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        let mut chunk = Vec::with_capacity(self.chunk_bytes);
+        file.take(self.chunk_bytes as u64).read_to_end(&mut chunk).map_err(|e| e.to_string())?;
+        let next = offset + chunk.len() as u64;
+        Ok(json!({
+            "path": raw,
+            "offset": offset,
+            "size": size,
+            "data": B64.encode(&chunk),
+            "eof": next >= size,
+        }))
+    }
+
+    /// `write_file {path, offset, data, final?}` → chunks are staged next to the target and
+    /// renamed into place when `final` is true, so a partial upload never replaces the file.
+    /// The first chunk (offset 0) creates the staging file afresh and later ones only open
+    /// it as a regular file, so a symlink planted in its place is never written through.
+    pub(crate) fn write_file(&self, args: &Value) -> Result<Value, ControlError> {
+        if !self.allow_write {
+            return Err(ControlError::not_found("write_file is disabled"));
+        }
+        let raw = path_arg(args)?;
+        let offset = args.get("offset").and_then(|o| o.as_u64()).unwrap_or(0);
+        let data = args.get("data").and_then(|d| d.as_str()).unwrap_or("");
+        let finish = args.get("final").and_then(|f| f.as_bool()).unwrap_or(false);
+        let bytes = B64.decode(data).map_err(|e| ControlError::invalid_args(format!("invalid base64: {}", e)))?;
+        let path = self.resolve(raw)?;
+        let staging = staging_path(&path);
+
+        let end = offset + bytes.len() as u64;
+        if end > self.max_file_bytes {
+            return Err(ControlError::invalid_args(format!("file too large: {} bytes (max {})", end, self.max_file_bytes)));
+        }
+        let mut file = open_staging(&staging, offset).map_err(|e| io_error(raw, e))?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        file.write_all(&bytes).map_err(|e| e.to_string())?;
+        drop(file);
+        if finish {
+            fs::rename(&staging, &path).map_err(|e| io_error(raw, e))?;
+        }
+        Ok(json!({"path": raw, "written": bytes.len(), "size": end, "complete": finish}))
+    }
+}
+
+fn path_arg(args: &Value) -> Result<&str, ControlError> {
+    args.get("path").and_then(|p| p.as_str()).ok_or_else(|| ControlError::invalid_args("missing args.path"))
+}
+
+/// A missing file is `NOT_FOUND`; other I/O failures keep the default code.
+fn io_error(raw: &str, e: io::Error) -> ControlError {
+    match e.kind() {
+        io::ErrorKind::NotFound => ControlError::not_found(format!("{}: {}", raw, e)),
+        _ => format!("{}: {}", raw, e).into(),
+    }
+}
+
+fn open_staging(staging: &Path, offset: u64) -> io::Result<fs::File> {
+    if offset == 0 {
+        // Removing a leftover (or planted) entry unlinks it rather than following it, and
+        // `create_new` refuses to open through anything that reappears in its place.
+        match fs::remove_file(staging) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        return OpenOptions::new().write(true).create_new(true).open(staging);
+    }
+    if !fs::symlink_metadata(staging)?.file_type().is_file() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "staging file is not a regular file"));
+    }
+    OpenOptions::new().write(true).open(staging)
+}
+
+fn staging_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".ariapart");
+    path.with_file_name(name)
+}
@@ -0,0 +1,66 @@
+//! Minimal HTTP endpoint (feature `health-endpoint`) for liveness probes and stats scraping.
+//! It reads one request head per connection, answers, and closes; there is no keep-alive,
+//! and a peer that doesn't finish its request within `READ_TIMEOUT` is dropped.
+
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::BridgeClient;
+
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// Slow or idle probes would otherwise each hold a task and a socket forever.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl BridgeClient {
+    /// Serve `GET /healthz` (200 while connected, 503 otherwise) and `GET /stats`
+    /// ([`BridgeStats`](crate::BridgeStats) as JSON) over plain HTTP for probes and
+    /// load balancers. Runs until accepting fails.
+    pub async fn serve_health(&self, addr: impl ToSocketAddrs) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let client = self.clone();
+            tokio::spawn(async move {
+                let _ = client.answer_health(stream).await;
+            });
+        }
+    }
+
+    async fn answer_health(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut req = Vec::new();
+        let mut chunk = [0u8; 1024];
+        let read = async {
+            while !req.windows(4).any(|w| w == b"\r\n\r\n") && req.len() < MAX_REQUEST_BYTES {
+                let n = stream.read(&mut chunk).await?;
+                if n == 0 {
+                    break;
+                }
+                req.extend_from_slice(&chunk[..n]);
+            }
+            Ok::<_, std::io::Error>(())
+        };
+        tokio::time::timeout(READ_TIMEOUT, read).await.map_err(|_| std::io::ErrorKind::TimedOut)??;
+        let head = String::from_utf8_lossy(&req);
+        let mut parts = head.split_whitespace();
+        let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+        let stats = self.stats();
+        let (status, body) = match (method, path) {
+            ("GET", "/healthz") if stats.connected => ("200 OK", json!({"status":"ok"})),
+            ("GET", "/healthz") => ("503 Service Unavailable", json!({"status":"disconnected"})),
+            ("GET", "/stats") => ("200 OK", serde_json::to_value(&stats).unwrap_or_default()),
+            _ => ("404 Not Found", json!({"error":"not found"})),
+        };
+        let body = body.to_string();
+        let resp = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(resp.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
@@ -0,0 +1,139 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use crate::{now_ms, BridgeClient};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+static FREED_BYTES: AtomicU64 = AtomicU64::new(0);
+static PEAK_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Global allocator wrapper that keeps allocation counters for `heap_stats`.
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOC: aria_bridge_client::CountingAllocator = aria_bridge_client::CountingAllocator::new(std::alloc::System);
+/// ```
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl<A> CountingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+fn record_alloc(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    let allocated = ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+    let live = allocated.saturating_sub(FREED_BYTES.load(Ordering::Relaxed));
+    PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+}
+
+fn record_dealloc(size: usize) {
+    DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    FREED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Snapshot of the [`CountingAllocator`] counters.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HeapStats {
+    pub allocations: u64,
+    pub deallocations: u64,
+    pub allocated_bytes: u64,
+    pub freed_bytes: u64,
+    pub live_bytes: u64,
+    pub peak_bytes: u64,
+}
+
+impl HeapStats {
+    pub fn current() -> Self {
+        let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let freed_bytes = FREED_BYTES.load(Ordering::Relaxed);
+        Self {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+            allocated_bytes,
+            freed_bytes,
+            live_bytes: allocated_bytes.saturating_sub(freed_bytes),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    /// `heap_stats` control result; `installed` is false when the wrapper isn't the global allocator.
+    pub(crate) fn to_json(self) -> Value {
+        json!({
+            "installed": self.allocations > 0,
+            "allocations": self.allocations,
+            "deallocations": self.deallocations,
+            "liveAllocations": self.allocations.saturating_sub(self.deallocations),
+            "allocatedBytes": self.allocated_bytes,
+            "freedBytes": self.freed_bytes,
+            "liveBytes": self.live_bytes,
+            "peakBytes": self.peak_bytes,
+        })
+    }
+}
+
+/// Whether [`CountingAllocator`] is the global allocator: it has recorded an allocation.
+pub(crate) fn installed() -> bool {
+    ALLOCATIONS.load(Ordering::Relaxed) > 0
+}
+
+/// Periodically report live heap usage as `metric` gauges.
+pub(crate) fn spawn(client: BridgeClient, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = time::interval(every);
+        loop {
+            ticker.tick().await;
+            let stats = HeapStats::current();
+            let ts = now_ms();
+            for (name, value) in [
+                ("heap.live_bytes", stats.live_bytes),
+                ("heap.live_allocations", stats.allocations.saturating_sub(stats.deallocations)),
+                ("heap.peak_bytes", stats.peak_bytes),
+            ] {
+                client.enqueue(json!({"type":"metric","kind":"gauge","name":name,"value":value,"timestamp":ts}));
+            }
+        }
+    })
+}
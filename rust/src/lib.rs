@@ -1,22 +1,150 @@
-use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
 use futures_util::{SinkExt, StreamExt};
-use rand::Rng;
+use serde::Serialize;
 use serde_json::{json, Value};
 use thiserror::Error;
-use tokio::net::TcpStream;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch, Notify};
 use tokio::time;
-use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+use tokio_tungstenite::tungstenite::Message;
 
-pub const PROTOCOL_VERSION: u64 = 2;
+use control::{control_response, Dispatch, InFlight};
+use protocol::{AuthSuccess, BridgeMessage, ControlRequest, HostMessage};
+
+mod ack;
+mod attachment;
+mod backoff;
+mod batch;
+pub mod blocking;
+pub mod build_script;
+mod capability;
+mod capture;
+mod clock;
+#[cfg(feature = "process")]
+mod command;
+mod compression;
+mod control;
+mod crash;
+mod debug_meta;
+mod early;
+mod env_snapshot;
+mod error_report;
+mod eval;
+mod failover;
+mod fallback;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod file_transfer;
+#[cfg(feature = "heap-stats")]
+mod heap_stats;
+#[cfg(feature = "health-endpoint")]
+mod health;
+mod lifecycle;
+#[cfg(unix)]
+mod local_broker;
+#[cfg(feature = "log")]
+mod log_backend;
+mod log_collection;
+mod manager;
+mod metadata;
+mod metrics;
+mod panic_hook;
+mod persistence;
+mod project;
+pub mod protocol;
+#[cfg(feature = "python")]
+mod python;
+mod random;
+mod rotating_file;
+mod rpc;
+mod routing;
+mod scheduler;
+mod sink;
+mod source;
+mod state;
+mod stats;
+#[cfg(all(unix, feature = "stdio-capture"))]
+mod stdio_capture;
+mod subscription;
+mod task_dump;
+#[cfg(feature = "rustls")]
+mod tls;
+mod trace;
+#[cfg(feature = "tracing")]
+mod tracing_layer;
+pub mod transport;
+mod watchdog;
+#[cfg(feature = "system-metrics")]
+mod system_metrics;
+
+pub use ack::SyncStatus;
+pub use attachment::{Snapshot, SnapshotProvider, ATTACHMENT_CHUNK_BYTES};
+pub use backoff::{BackoffFn, BackoffPolicy, FlapBreakerConfig, FLAP_COOL_OFF_MS, FLAP_MAX_FLAPS, FLAP_MIN_SESSION_MS};
+pub use batch::{BatchConfig, BATCH_MAX_BYTES, BATCH_MAX_EVENTS};
+pub use capability::Capability;
+pub use capture::{CaptureReader, CaptureRecord, Direction, FrameKind, CAPTURE_MAGIC};
+#[cfg(feature = "process")]
+pub use command::BridgeCommand;
+pub use compression::COMPRESSION_THRESHOLD_BYTES;
+pub use control::{control_args, control_result, ControlContext, ControlError, CONTROL_TIMEOUT_MS};
+pub use crash::{CrashReportConfig, CRASH_REPORT_MAX_BYTES};
+pub use early::{early_event, EARLY_BUFFER_LIMIT};
+pub use env_snapshot::{EnvSnapshotConfig, DEFAULT_REDACT_KEYS, REDACTED};
+pub use eval::{EvalConfig, Evaluator, EVAL_TIMEOUT_MS};
+pub use failover::FAILBACK_PROBE_MS;
+pub use fallback::{FallbackConfig, FALLBACK_MAX_FILES, FALLBACK_MAX_FILE_BYTES, FALLBACK_THRESHOLD_MS};
+pub use file_transfer::{FileTransferConfig, FILE_TRANSFER_CHUNK_BYTES, FILE_TRANSFER_MAX_BYTES};
+#[cfg(feature = "heap-stats")]
+pub use heap_stats::{CountingAllocator, HeapStats};
+pub use lifecycle::{ConnectInfo, DisconnectReason};
+#[cfg(feature = "log")]
+pub use log_backend::BridgeLogger;
+pub use log_collection::{LogCollectionConfig, LOG_COLLECTION_MAX_FILE_BYTES, LOG_COLLECTION_MAX_TOTAL_BYTES};
+pub use manager::BridgeManager;
+pub use metadata::BuildInfo;
+pub use metrics::METRIC_WINDOW_MS;
+pub use panic_hook::PANIC_FLUSH_TIMEOUT_MS;
+pub use persistence::PERSIST_MAX_AGE_MS;
+pub use project::ProjectHandle;
+pub use random::RandomSource;
+pub use routing::{RouteAction, RouteRule};
+pub use rpc::REQUEST_TIMEOUT_MS;
+pub use scheduler::{Priority, SendOptions};
+pub use sink::{EventSink, FileSink, Sink, StdoutSink};
+pub use source::SourceLocation;
+pub use state::ConnectionState;
+pub use stats::{BridgeStats, RTT_WINDOW};
+pub use task_dump::TrackedTask;
+#[cfg(feature = "rustls")]
+pub use tls::TlsConfig;
+pub use trace::SpanGuard;
+#[cfg(feature = "tracing")]
+pub use tracing_layer::{tracing_layer, BridgeLayer};
+
+/// Highest protocol version this client speaks; hosts that advertise an older one in
+/// `auth_success` (or none, which means 1) get that version instead (see
+/// [`BridgeClient::protocol_version`]).
+pub const PROTOCOL_VERSION: u64 = 3;
+/// First version with `batch` frames and `seq`/`ack` delivery; below it both are off.
+pub const BATCH_ACK_PROTOCOL_VERSION: u64 = 3;
 pub const HEARTBEAT_INTERVAL_MS: u64 = 15_000;
 pub const HEARTBEAT_TIMEOUT_MS: u64 = 30_000;
 pub const BACKOFF_INITIAL_MS: u64 = 1_000;
 pub const BACKOFF_MAX_MS: u64 = 30_000;
 pub const BUFFER_LIMIT: usize = 200;
+pub const CLOSE_HANDSHAKE_TIMEOUT_MS: u64 = 1_000;
+
+/// Close codes sent in the WebSocket Close frame so hosts can tell intentional exits from crashes.
+pub const CLOSE_NORMAL: u16 = 1000;
+pub const CLOSE_GOING_AWAY: u16 = 1001;
+pub const CLOSE_INTERNAL_ERROR: u16 = 1011;
+pub const CLOSE_HEARTBEAT_TIMEOUT: u16 = 4000;
 
 #[derive(Debug, Error)]
 pub enum BridgeError {
@@ -28,11 +156,68 @@ pub enum BridgeError {
     Json(#[from] serde_json::Error),
     #[error("auth_success timeout")]
     AuthTimeout,
+    /// The host rejected the secret; retrying won't help.
+    #[error("auth failed: {0}")]
+    AuthFailed(String),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("disconnected: {0}")]
+    Disconnected(DisconnectReason),
+    /// The host answered a [`BridgeClient::request`] with an error.
+    #[error("request failed: {0}")]
+    Request(ControlError),
+    #[error("request timed out")]
+    RequestTimeout,
+    #[error("shutdown did not finish within the deadline")]
+    ShutdownTimeout,
+    #[error("unknown performance mark: {0}")]
+    UnknownMark(String),
+    /// The TLS settings couldn't be used (bad PEM, mismatched key).
+    #[error("tls: {0}")]
+    Tls(String),
+    /// No certificate the host presented matches `TlsConfig::spki_pins`; carries the
+    /// leaf's SPKI hash. Fatal, like [`BridgeError::AuthFailed`].
+    #[error("certificate pin mismatch (host key sha256/{0})")]
+    PinMismatch(String),
+    /// `max_reconnect_attempts` or `max_total_downtime_ms` ran out; `last` is the final
+    /// attempt's error.
+    #[error("gave up reconnecting after {attempts} failed attempts ({downtime_ms}ms down): {last}")]
+    ReconnectExhausted { attempts: u32, downtime_ms: u64, last: Box<BridgeError> },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct BridgeConfig {
     pub url: String,
+    /// Endpoints in priority order, used instead of `url` when non-empty. A failed attempt
+    /// moves on to the next one; while on a standby, the primary is probed every
+    /// `failback_probe_ms` and the client switches back once it answers.
+    pub urls: Vec<String>,
+    pub failback_probe_ms: u64,
+    /// Where a `redirect` from the host may move the bridge, as `host[:port]` entries (a bare
+    /// host means the target scheme's default port); empty allows only the current host and
+    /// port. Redirects are only followed to `ws://`, `wss://`, or `tcp://` URLs, and never
+    /// from `wss://` to a plaintext scheme; others are ignored and the current connection
+    /// stays up.
+    pub redirect_hosts: Vec<String>,
+    /// Host name to address overrides for the built-in transports, checked before any
+    /// resolver (the port still comes from the URL); for split-horizon DNS. Behind an `http://`
+    /// or `socks5h://` proxy the proxy resolves names, so neither these nor a custom resolver
+    /// apply.
+    pub resolve_overrides: HashMap<String, IpAddr>,
+    /// Reach the host through this proxy: `http://[user:pass@]host:port` (HTTP `CONNECT`) or
+    /// `socks5://[user:pass@]host:port`. Applies to the built-in `ws://`, `wss://`, and `tcp://`
+    /// transports. `socks5://` resolves the host name locally (with `resolve_overrides` and
+    /// any [`set_resolver`](BridgeClient::set_resolver) resolver) and sends the proxy an
+    /// address; `socks5h://` and `http://` send the name and leave resolution to the proxy.
+    pub proxy: Option<String>,
+    /// Extra headers on the WebSocket upgrade request, e.g. `Authorization` or `X-Api-Key`
+    /// for gateways that check them before the auth message; a repeated name is sent each
+    /// time. Built-in `ws://` and `wss://` connections only.
+    pub headers: Vec<(String, String)>,
+    /// Private CA, client certificate, or custom rustls config for `wss://` (requires the
+    /// `rustls` feature).
+    #[cfg(feature = "rustls")]
+    pub tls: Option<TlsConfig>,
     pub secret: String,
     pub project_id: Option<String>,
     pub capabilities: Vec<String>,
@@ -40,32 +225,279 @@ pub struct BridgeConfig {
     pub heartbeat_timeout_ms: u64,
     pub backoff_initial_ms: u64,
     pub backoff_max_ms: u64,
+    /// Reconnect schedule; `None` is exponential from `backoff_initial_ms` to
+    /// `backoff_max_ms` with up to 50% jitter.
+    pub backoff: Option<BackoffPolicy>,
+    /// Start the schedule over once a connection has stayed up this long. By default it
+    /// only restarts after a clean close.
+    pub backoff_reset_after_ms: Option<u64>,
+    /// Make `run_with_reconnect` return [`BridgeError::ReconnectExhausted`] after this many
+    /// consecutive failed connection attempts.
+    pub max_reconnect_attempts: Option<u32>,
+    /// Likewise once the client has been unable to connect for this long.
+    pub max_total_downtime_ms: Option<u64>,
+    /// Treat connections dropped right after connecting as failures and pause after
+    /// repeated ones; see [`FlapBreakerConfig`].
+    pub flap_breaker: Option<FlapBreakerConfig>,
     pub buffer_limit: usize,
+    /// Discard buffered events older than this when flushing, so a long outage doesn't
+    /// replay stale ones (errors and high-priority events are kept); discarded events are
+    /// counted in the drop notice.
+    pub buffer_ttl_ms: Option<u64>,
+    /// Sample process CPU/RSS/FDs/threads at this interval while connected
+    /// (requires the `system-metrics` feature).
+    pub system_metrics_interval_ms: Option<u64>,
+    /// Application build details reported in hello; see [`build_info!`].
+    pub build_info: Option<BuildInfo>,
+    /// Reported in hello metadata as `appVersion`.
+    pub app_version: Option<String>,
+    /// Extra hello metadata fields; built-in fields of the same name take precedence.
+    pub extra_metadata: HashMap<String, Value>,
+    /// Enables the allowlisted `read_file` / `write_file` control actions.
+    pub file_transfer: Option<FileTransferConfig>,
+    /// Enables the `get_env` control action (values redacted per the config).
+    pub env_snapshot: Option<EnvSnapshotConfig>,
+    /// Report `heap.*` gauges from [`CountingAllocator`] at this interval while connected
+    /// (requires the `heap-stats` feature).
+    pub heap_stats_interval_ms: Option<u64>,
+    /// Upload minidumps from a previous crash when the client starts.
+    pub crash_reports: Option<CrashReportConfig>,
+    /// Enables the `collect_logs` control action over these directories.
+    pub log_collection: Option<LogCollectionConfig>,
+    /// Report `hang_suspected` when `heartbeat_app()` isn't called within this window.
+    pub watchdog_timeout_ms: Option<u64>,
+    /// Ordered routing rules deciding which events this client forwards; see [`RouteRule`].
+    pub routes: Vec<RouteRule>,
+    /// Spill events to a local rotating file after a long disconnect.
+    pub fallback: Option<FallbackConfig>,
+    /// zstd-compress frames at least this large when the host accepts it
+    /// (requires the `compression` feature).
+    pub compression_threshold_bytes: Option<usize>,
+    /// Aggregation window for `send_metric`; 0 sends every sample individually.
+    pub metric_window_ms: u64,
+    /// Add `serverTimestamp` (local `timestamp` shifted by [`BridgeClient::clock_offset_ms`])
+    /// to outgoing events. Hosts that answer `time_sync` get host-clock `timestamp`s anyway.
+    pub server_timestamps: bool,
+    /// Disconnect after this long without outgoing events; the next event reconnects.
+    pub idle_disconnect_ms: Option<u64>,
+    /// Append every raw frame sent or received to this file; read it with [`CaptureReader`].
+    pub wire_capture: Option<PathBuf>,
+    /// Journal buffered events in this directory so the next run delivers whatever a crash or
+    /// restart left undelivered (requires the `persistence` feature). Give clients that run
+    /// at the same time their own directory.
+    pub persist_dir: Option<PathBuf>,
+    /// Number events with `seq` and keep them until the host replies `{type:"ack", seq}`;
+    /// unacknowledged events are re-sent after a reconnect. See [`BridgeClient::sync_status`].
+    pub acks: bool,
+    /// Answer async control requests still running after this long with a `TIMEOUT` error;
+    /// a request's own `timeoutMs` takes precedence.
+    pub control_timeout_ms: Option<u64>,
+    /// How long [`BridgeClient::request`] waits for the host's `bridge_result`.
+    pub request_timeout_ms: u64,
+    /// Put the latest measured round trip in heartbeat pings as `rttMs`.
+    pub report_rtt: bool,
+    /// Send queued events in `batch` frames instead of one frame each.
+    pub batch: Option<BatchConfig>,
+    /// While connected, collect new events for this long before writing them together;
+    /// 0 writes each event as soon as it is enqueued.
+    pub flush_interval_ms: u64,
+    /// Per-`type` caps within `buffer_limit` (e.g. `console: 150`); a type over its cap
+    /// evicts its own oldest, lowest-priority events first.
+    pub buffer_type_limits: HashMap<String, usize>,
+    /// Merged into every event's `tags` (deployment, environment...); an event's own tags win.
+    pub tags: HashMap<String, String>,
 }
 
 impl Default for BridgeConfig {
     fn default() -> Self {
         Self {
             url: "ws://localhost:9876".into(),
+            urls: Vec::new(),
+            failback_probe_ms: FAILBACK_PROBE_MS,
+            redirect_hosts: Vec::new(),
+            resolve_overrides: HashMap::new(),
+            proxy: None,
+            headers: Vec::new(),
+            #[cfg(feature = "rustls")]
+            tls: None,
             secret: "dev-secret".into(),
             project_id: None,
-            capabilities: vec!["console".into(), "error".into()],
+            capabilities: vec!["console".into(), "error".into(), "performance".into(), "metric".into(), "trace".into()],
             heartbeat_interval_ms: HEARTBEAT_INTERVAL_MS,
             heartbeat_timeout_ms: HEARTBEAT_TIMEOUT_MS,
             backoff_initial_ms: BACKOFF_INITIAL_MS,
             backoff_max_ms: BACKOFF_MAX_MS,
+            backoff: None,
+            backoff_reset_after_ms: None,
+            max_reconnect_attempts: None,
+            max_total_downtime_ms: None,
+            flap_breaker: None,
             buffer_limit: BUFFER_LIMIT,
+            buffer_ttl_ms: None,
+            system_metrics_interval_ms: None,
+            build_info: None,
+            app_version: None,
+            extra_metadata: HashMap::new(),
+            file_transfer: None,
+            env_snapshot: None,
+            heap_stats_interval_ms: None,
+            crash_reports: None,
+            log_collection: None,
+            watchdog_timeout_ms: None,
+            routes: Vec::new(),
+            fallback: None,
+            compression_threshold_bytes: None,
+            metric_window_ms: METRIC_WINDOW_MS,
+            server_timestamps: false,
+            idle_disconnect_ms: None,
+            wire_capture: None,
+            persist_dir: None,
+            acks: false,
+            control_timeout_ms: Some(CONTROL_TIMEOUT_MS),
+            request_timeout_ms: REQUEST_TIMEOUT_MS,
+            report_rtt: false,
+            batch: None,
+            flush_interval_ms: 0,
+            buffer_type_limits: HashMap::new(),
+            tags: HashMap::new(),
         }
     }
 }
 
-type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+impl std::fmt::Debug for BridgeConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // No credentials: not the secret, header values, or userinfo and query values in
+        // URLs (proxy URLs only lose their userinfo).
+        let headers: Vec<(&str, &str)> = self.headers.iter().map(|(name, _)| (name.as_str(), REDACTED)).collect();
+        let mut f = f.debug_struct("BridgeConfig");
+        f.field("url", &env_snapshot::redact_url(&self.url))
+            .field("urls", &self.urls.iter().map(|url| env_snapshot::redact_url(url)).collect::<Vec<_>>())
+            .field("failback_probe_ms", &self.failback_probe_ms)
+            .field("redirect_hosts", &self.redirect_hosts)
+            .field("resolve_overrides", &self.resolve_overrides)
+            .field("proxy", &self.proxy.as_deref().map(env_snapshot::strip_userinfo))
+            .field("headers", &headers);
+        #[cfg(feature = "rustls")]
+        f.field("tls", &self.tls);
+        f.field("secret", &REDACTED)
+            .field("project_id", &self.project_id)
+            .field("capabilities", &self.capabilities)
+            .field("heartbeat_interval_ms", &self.heartbeat_interval_ms)
+            .field("heartbeat_timeout_ms", &self.heartbeat_timeout_ms)
+            .field("backoff_initial_ms", &self.backoff_initial_ms)
+            .field("backoff_max_ms", &self.backoff_max_ms)
+            .field("backoff", &self.backoff)
+            .field("backoff_reset_after_ms", &self.backoff_reset_after_ms)
+            .field("max_reconnect_attempts", &self.max_reconnect_attempts)
+            .field("max_total_downtime_ms", &self.max_total_downtime_ms)
+            .field("flap_breaker", &self.flap_breaker)
+            .field("buffer_limit", &self.buffer_limit)
+            .field("buffer_ttl_ms", &self.buffer_ttl_ms)
+            .field("system_metrics_interval_ms", &self.system_metrics_interval_ms)
+            .field("build_info", &self.build_info)
+            .field("app_version", &self.app_version)
+            .field("extra_metadata", &self.extra_metadata)
+            .field("file_transfer", &self.file_transfer)
+            .field("env_snapshot", &self.env_snapshot)
+            .field("heap_stats_interval_ms", &self.heap_stats_interval_ms)
+            .field("crash_reports", &self.crash_reports)
+            .field("log_collection", &self.log_collection)
+            .field("watchdog_timeout_ms", &self.watchdog_timeout_ms)
+            .field("routes", &self.routes)
+            .field("fallback", &self.fallback)
+            .field("compression_threshold_bytes", &self.compression_threshold_bytes)
+            .field("metric_window_ms", &self.metric_window_ms)
+            .field("server_timestamps", &self.server_timestamps)
+            .field("idle_disconnect_ms", &self.idle_disconnect_ms)
+            .field("wire_capture", &self.wire_capture)
+            .field("persist_dir", &self.persist_dir)
+            .field("acks", &self.acks)
+            .field("control_timeout_ms", &self.control_timeout_ms)
+            .field("request_timeout_ms", &self.request_timeout_ms)
+            .field("report_rtt", &self.report_rtt)
+            .field("batch", &self.batch)
+            .field("flush_interval_ms", &self.flush_interval_ms)
+            .field("buffer_type_limits", &self.buffer_type_limits)
+            .field("tags", &self.tags)
+            .finish()
+    }
+}
+
+type WsStream = transport::Connection;
+
+#[derive(Clone)]
+struct CloseRequest {
+    code: u16,
+    reason: String,
+    /// How long to keep flushing and waiting for the host's Close reply.
+    drain_timeout: Duration,
+}
+
+/// How a connection that ended without an error was closed.
+enum Session {
+    Closed,
+    Idle,
+    Redirected,
+}
+
+/// Frames queued for the connection's writer task.
+enum Outbound {
+    Json(Value),
+    Close(u16, String),
+}
+type ControlHandler = Arc<dyn Fn(Value) -> Result<Value, ControlError> + Send + Sync>;
 
 pub struct BridgeClient {
     cfg: BridgeConfig,
     buffer: Arc<Mutex<VecDeque<Value>>>,
-    dropped: Arc<Mutex<usize>>,
-    control_handler: Arc<Mutex<Option<Arc<dyn Fn(Value) -> Result<Value, String> + Send + Sync>>>>,
+    dropped: Arc<Mutex<stats::DropCounts>>,
+    control_handler: Arc<Mutex<Option<ControlHandler>>>,
+    actions: Arc<Mutex<HashMap<String, control::Action>>>,
+    marks: Arc<Mutex<HashMap<String, (Instant, u64)>>>,
+    snapshot_provider: Arc<Mutex<Option<Arc<dyn SnapshotProvider>>>>,
+    evaluator: Arc<Mutex<eval::EvalSlot>>,
+    tasks: task_dump::TaskRegistry,
+    watchdog: watchdog::WatchdogState,
+    extensions: Arc<Mutex<Vec<Arc<dyn Capability>>>>,
+    fallback: Arc<Mutex<fallback::FallbackState>>,
+    sinks: Arc<Mutex<Vec<Arc<dyn Sink>>>>,
+    deliveries: sink::Deliveries,
+    hooks: Arc<Mutex<lifecycle::Hooks>>,
+    rpc: Arc<Mutex<rpc::RpcState>>,
+    metrics: metrics::MetricState,
+    clock: Arc<Mutex<clock::ClockSync>>,
+    stats: Arc<Mutex<stats::Counters>>,
+    acks: Arc<Mutex<ack::AckState>>,
+    random: Arc<Mutex<Option<RandomSource>>>,
+    wire: capture::WireCapture,
+    pending_log: persistence::PendingLog,
+    reported_dumps: crash::ReportedDumps,
+    unwritten: Unwritten,
+    transport: Arc<Mutex<Option<Arc<dyn transport::Transport>>>>,
+    resolver: Arc<Mutex<Option<Arc<dyn transport::resolve::Resolve>>>>,
+    session_token: Arc<Mutex<Option<String>>>,
+    event_ids: Arc<AtomicU64>,
+    protocol: Arc<AtomicU64>,
+    capabilities: Arc<Mutex<Vec<String>>>,
+    subscriptions: Arc<Mutex<subscription::Subscriptions>>,
+    /// Where to connect: the current entry of `cfg.urls` (or `cfg.url`), or a `redirect` target.
+    url: Arc<Mutex<String>>,
+    /// Server-directed delay for the next reconnect, from `throttle` or a close frame.
+    retry_after: Arc<Mutex<Option<Duration>>>,
+    /// The current connection's writer, for protocol frames that must not go through the
+    /// event buffer; `None` while disconnected.
+    live: Arc<Mutex<Option<OutboundSender>>>,
+    /// Protocol frames waiting for the next connection (`bridge_request`s sent while down).
+    control_lane: Arc<Mutex<VecDeque<Value>>>,
+    close_request: Arc<Mutex<Option<CloseRequest>>>,
+    close_notify: Arc<Notify>,
+    state: Arc<watch::Sender<ConnectionState>>,
+    wake: Arc<Notify>,
+    started_at: Instant,
+    session_id: Arc<str>,
+    /// Held by every handle except the [`detach`](Self::detach)ed ones background loops run
+    /// on, so those loops can tell when the application is done with the client.
+    handles: Arc<()>,
 }
 
 impl Clone for BridgeClient {
@@ -75,80 +507,546 @@ impl Clone for BridgeClient {
             buffer: self.buffer.clone(),
             dropped: self.dropped.clone(),
             control_handler: self.control_handler.clone(),
+            actions: self.actions.clone(),
+            marks: self.marks.clone(),
+            snapshot_provider: self.snapshot_provider.clone(),
+            evaluator: self.evaluator.clone(),
+            tasks: self.tasks.clone(),
+            watchdog: self.watchdog.clone(),
+            extensions: self.extensions.clone(),
+            fallback: self.fallback.clone(),
+            sinks: self.sinks.clone(),
+            deliveries: self.deliveries.clone(),
+            hooks: self.hooks.clone(),
+            rpc: self.rpc.clone(),
+            metrics: self.metrics.clone(),
+            clock: self.clock.clone(),
+            stats: self.stats.clone(),
+            acks: self.acks.clone(),
+            random: self.random.clone(),
+            wire: self.wire.clone(),
+            pending_log: self.pending_log.clone(),
+            reported_dumps: self.reported_dumps.clone(),
+            unwritten: self.unwritten.clone(),
+            transport: self.transport.clone(),
+            resolver: self.resolver.clone(),
+            session_token: self.session_token.clone(),
+            event_ids: self.event_ids.clone(),
+            protocol: self.protocol.clone(),
+            capabilities: self.capabilities.clone(),
+            subscriptions: self.subscriptions.clone(),
+            url: self.url.clone(),
+            retry_after: self.retry_after.clone(),
+            close_request: self.close_request.clone(),
+            close_notify: self.close_notify.clone(),
+            state: self.state.clone(),
+            wake: self.wake.clone(),
+            live: self.live.clone(),
+            control_lane: self.control_lane.clone(),
+            started_at: self.started_at,
+            session_id: self.session_id.clone(),
+            handles: self.handles.clone(),
         }
     }
 }
 
 impl BridgeClient {
     pub fn new(cfg: BridgeConfig) -> Self {
-        Self {
+        let fallback = fallback::FallbackState::new(cfg.fallback.as_ref());
+        let wire = capture::WireCapture::open(cfg.wire_capture.as_deref());
+        let session_id: Arc<str> = format!("{:016x}{:016x}", random::next_u64(), random::next_u64()).into();
+        let capabilities = cfg.capabilities.clone();
+        let url = cfg.urls.first().unwrap_or(&cfg.url).clone();
+        let pending_log = persistence::PendingLog::open(cfg.persist_dir.as_deref(), cfg.project_id.as_deref(), &session_id);
+        let client = Self {
             cfg,
             buffer: Arc::new(Mutex::new(VecDeque::new())),
-            dropped: Arc::new(Mutex::new(0)),
+            dropped: Arc::new(Mutex::new(stats::DropCounts::new())),
             control_handler: Arc::new(Mutex::new(None)),
+            actions: Arc::new(Mutex::new(HashMap::new())),
+            marks: Arc::new(Mutex::new(HashMap::new())),
+            snapshot_provider: Arc::new(Mutex::new(None)),
+            evaluator: Arc::new(Mutex::new(None)),
+            tasks: Arc::new(Mutex::new(HashMap::new())),
+            watchdog: Arc::new(Mutex::new(None)),
+            extensions: Arc::new(Mutex::new(Vec::new())),
+            fallback,
+            sinks: Arc::new(Mutex::new(Vec::new())),
+            deliveries: sink::Deliveries::default(),
+            hooks: Arc::new(Mutex::new(lifecycle::Hooks::default())),
+            rpc: Arc::new(Mutex::new(rpc::RpcState::default())),
+            metrics: Arc::new(Mutex::new(metrics::MetricWindow::default())),
+            clock: Arc::new(Mutex::new(clock::ClockSync::default())),
+            stats: Arc::new(Mutex::new(stats::Counters::default())),
+            acks: Arc::new(Mutex::new(ack::AckState::default())),
+            random: Arc::new(Mutex::new(None)),
+            wire,
+            pending_log,
+            reported_dumps: crash::ReportedDumps::default(),
+            unwritten: Unwritten::default(),
+            transport: Arc::new(Mutex::new(None)),
+            resolver: Arc::new(Mutex::new(None)),
+            session_token: Arc::new(Mutex::new(None)),
+            event_ids: Arc::new(AtomicU64::new(0)),
+            protocol: Arc::new(AtomicU64::new(PROTOCOL_VERSION)),
+            capabilities: Arc::new(Mutex::new(capabilities)),
+            subscriptions: Arc::new(Mutex::new(subscription::Subscriptions::new())),
+            url: Arc::new(Mutex::new(url)),
+            retry_after: Arc::new(Mutex::new(None)),
+            close_request: Arc::new(Mutex::new(None)),
+            close_notify: Arc::new(Notify::new()),
+            state: Arc::new(watch::Sender::new(ConnectionState::Closed)),
+            wake: Arc::new(Notify::new()),
+            live: Arc::new(Mutex::new(None)),
+            control_lane: Arc::new(Mutex::new(VecDeque::new())),
+            started_at: Instant::now(),
+            session_id,
+            handles: Arc::new(()),
+        };
+        client.restore_persisted();
+        let (early_events, early_dropped) = early::take();
+        // Added to, not replaced: restoring persisted events may already have evicted some.
+        let mut dropped = client.dropped.lock().unwrap();
+        for (ty, n) in early_dropped {
+            *dropped.entry(ty).or_default() += n;
+        }
+        drop(dropped);
+        for ev in early_events {
+            client.enqueue(ev);
+        }
+        if let Some(crash_cfg) = &client.cfg.crash_reports {
+            client.report_pending_crashes(crash_cfg);
+        }
+        if let Some(ms) = client.cfg.watchdog_timeout_ms {
+            client.spawn_watchdog(Duration::from_millis(ms));
         }
+        client
+    }
+
+    /// A handle for a background loop that must not keep the client alive by itself, and a
+    /// token that goes dead once every other handle has been dropped.
+    pub(crate) fn detach(&self) -> (Self, Weak<()>) {
+        let handles = Arc::downgrade(&self.handles);
+        (Self { handles: Arc::new(()), ..self.clone() }, handles)
     }
 
     pub fn on_control<F>(&self, handler: F)
     where
-        F: Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+        F: Fn(Value) -> Result<Value, ControlError> + Send + Sync + 'static,
     {
         *self.control_handler.lock().unwrap() = Some(Arc::new(handler));
     }
 
+    /// Register the provider used for `snapshot` control requests; the result is streamed
+    /// back as `attachment` chunks and advertised under the `screenshot` capability.
+    pub fn set_snapshot_provider<P>(&self, provider: P)
+    where
+        P: SnapshotProvider + 'static,
+    {
+        *self.snapshot_provider.lock().unwrap() = Some(Arc::new(provider));
+    }
+
+    /// Enable the `eval` control action, dispatching allowlisted code to `evaluator`.
+    pub fn set_evaluator<E>(&self, cfg: EvalConfig, evaluator: E)
+    where
+        E: Evaluator + 'static,
+    {
+        *self.evaluator.lock().unwrap() = Some((cfg, Arc::new(evaluator)));
+    }
+
     pub async fn send_console(&self, level: &str, message: &str) {
         let ev = json!({"type":"console","level":level,"message":message,"timestamp":now_ms()});
         self.enqueue(ev);
     }
 
+    /// A `console` event with structured `fields` (request id, user, module...) alongside the message.
+    pub async fn send_log<T: Serialize>(&self, level: &str, message: &str, fields: T) -> Result<(), BridgeError> {
+        let fields = serde_json::to_value(fields)?;
+        let ev = json!({"type":"console","level":level,"message":message,"fields":fields,"timestamp":now_ms()});
+        self.enqueue(ev);
+        Ok(())
+    }
+
     pub async fn send_error(&self, message: &str) {
-        let ev = json!({"type":"error","message":message,"timestamp":now_ms()});
+        let ev = json!({"type":"error","message":message,"timestamp":now_ms(),"debug":debug_meta::debug_meta(&self.cfg)});
+        self.enqueue(ev);
+    }
+
+    /// Send a custom event: an object payload's fields become the event's fields (anything
+    /// else goes under `data`), with `type` and `timestamp` stamped on top.
+    pub async fn send_event<T: Serialize>(&self, event_type: &str, payload: T) -> Result<(), BridgeError> {
+        self.enqueue(custom_event(event_type, payload)?);
+        Ok(())
+    }
+
+    /// Record a named performance mark and emit it as a `performance` event.
+    pub async fn mark(&self, name: &str) {
+        let ts = now_ms();
+        self.marks.lock().unwrap().insert(name.to_string(), (Instant::now(), ts));
+        let ev = json!({"type":"performance","entryType":"mark","name":name,"startTime":ts,"timestamp":ts});
+        self.enqueue(ev);
+    }
+
+    /// Emit a `performance` measure spanning two previously recorded marks.
+    /// Returns the measured duration in milliseconds.
+    pub async fn measure(&self, name: &str, start_mark: &str, end_mark: &str) -> Result<f64, BridgeError> {
+        let (start, end) = {
+            let marks = self.marks.lock().unwrap();
+            let start = *marks.get(start_mark).ok_or_else(|| BridgeError::UnknownMark(start_mark.into()))?;
+            let end = *marks.get(end_mark).ok_or_else(|| BridgeError::UnknownMark(end_mark.into()))?;
+            (start, end)
+        };
+        let duration = end.0.saturating_duration_since(start.0).as_secs_f64() * 1000.0;
+        let ev = json!({
+            "type":"performance",
+            "entryType":"measure",
+            "name":name,
+            "startMark":start_mark,
+            "endMark":end_mark,
+            "startTime":start.1,
+            "duration":duration,
+            "timestamp":now_ms()
+        });
         self.enqueue(ev);
+        Ok(duration)
     }
 
-    fn enqueue(&self, ev: Value) {
-        let mut buf = self.buffer.lock().unwrap();
-        if buf.len() >= self.cfg.buffer_limit {
-            buf.pop_front();
-            *self.dropped.lock().unwrap() += 1;
+    fn hello_capabilities(&self) -> Vec<String> {
+        let mut caps = self.capabilities.lock().unwrap().clone();
+        if cfg!(feature = "system-metrics") && self.cfg.system_metrics_interval_ms.is_some() {
+            caps.push("system_metrics".into());
+        }
+        if self.cfg.file_transfer.is_some() {
+            caps.push("file_transfer".into());
+        }
+        if self.snapshot_provider.lock().unwrap().is_some() {
+            caps.push("screenshot".into());
+        }
+        if self.evaluator.lock().unwrap().is_some() {
+            caps.push("eval".into());
+        }
+        if cfg!(feature = "log-collection") && self.cfg.log_collection.is_some() {
+            caps.push("collect_logs".into());
+        }
+        if self.cfg.watchdog_timeout_ms.is_some() {
+            caps.push("watchdog".into());
+        }
+        if cfg!(feature = "compression") && self.cfg.compression_threshold_bytes.is_some() {
+            caps.push("zstd".into());
         }
-        buf.push_back(ev);
+        // Only once the allocator has counted something, i.e. it is the global allocator.
+        #[cfg(feature = "heap-stats")]
+        if heap_stats::installed() {
+            caps.push("heap_stats".into());
+        }
+        if self.batch_config().is_some() {
+            caps.push("batch".into());
+        }
+        for ext in self.extensions.lock().unwrap().iter() {
+            caps.push(ext.name().to_string());
+        }
+        caps
+    }
+
+    /// The endpoint the client connects to: `BridgeConfig::url`, the current failover
+    /// endpoint from `BridgeConfig::urls`, or the last `redirect` target.
+    pub fn url(&self) -> String {
+        self.url.lock().unwrap().clone()
+    }
+
+    /// Protocol version agreed with the host on the latest connection: the lower of
+    /// [`PROTOCOL_VERSION`] and the `protocol` the host advertised in `auth_success`.
+    pub fn protocol_version(&self) -> u64 {
+        self.protocol.load(Ordering::Relaxed)
     }
 
-    async fn flush_buffer(&self, ws: &mut WsStream) -> Result<(), BridgeError> {
-        let (pending, dropped) = {
+    pub(crate) fn batch_ack_negotiated(&self) -> bool {
+        self.protocol_version() >= BATCH_ACK_PROTOCOL_VERSION
+    }
+
+    fn batch_config(&self) -> Option<&BatchConfig> {
+        self.cfg.batch.as_ref().filter(|_| self.batch_ack_negotiated())
+    }
+
+    /// Random id for this client's lifetime, stamped on every event as `sessionId` and sent
+    /// in hello metadata, so the host can tell process runs apart.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    fn hello_metadata(&self) -> Value {
+        let mut meta = metadata::process_metadata(&self.cfg);
+        meta["sessionId"] = self.session_id().into();
+        if let Some(ext) = self.extension_metadata() {
+            meta["capabilities"] = ext;
+        }
+        meta
+    }
+
+    /// Also deliver every outgoing event to `sink` (file, stdout, custom), concurrently
+    /// with the other sinks.
+    pub fn add_sink<S>(&self, sink: S)
+    where
+        S: Sink + 'static,
+    {
+        self.sinks.lock().unwrap().push(Arc::new(sink));
+    }
+
+    /// Add `sessionId`, a unique `eventId`, and the configured global `tags`. Events that
+    /// already carry an `eventId` keep it.
+    pub(crate) fn stamp(&self, ev: &mut Value) {
+        let Some(obj) = ev.as_object_mut() else { return };
+        obj.entry("sessionId").or_insert_with(|| Value::from(&*self.session_id));
+        obj.entry("eventId").or_insert_with(|| {
+            let n = self.event_ids.fetch_add(1, Ordering::Relaxed) + 1;
+            Value::from(format!("{}-{}", self.session_id, n))
+        });
+        if self.cfg.tags.is_empty() {
+            return;
+        }
+        if let Some(tags) = obj.entry("tags").or_insert_with(|| json!({})).as_object_mut() {
+            for (k, v) in &self.cfg.tags {
+                tags.entry(k.as_str()).or_insert_with(|| Value::from(v.as_str()));
+            }
+        }
+    }
+
+    pub(crate) fn enqueue(&self, mut ev: Value) {
+        self.stamp(&mut ev);
+        if !routing::should_send(&self.cfg.routes, &ev) {
+            return;
+        }
+        let sinks = self.sinks.lock().unwrap().clone();
+        self.deliveries.start(sinks.iter().map(|sink| sink.deliver(&ev)).collect());
+        self.buffer_for_socket(ev);
+    }
+
+    /// Send a protocol frame (not an event) on the live connection, outside the event
+    /// buffer's eviction, quotas, persistence, and acks. While disconnected it is dropped,
+    /// or with `queue` held for the next connection.
+    pub(crate) fn send_protocol(&self, frame: Value, queue: bool) {
+        let live = self.live.lock().unwrap();
+        if live.as_ref().is_some_and(|tx| tx.send(frame.clone()).is_ok()) {
+            return;
+        }
+        if queue {
+            self.control_lane.lock().unwrap().push_back(frame);
+            // Also wakes an idle-suspended client so it reconnects.
+            self.wake.notify_one();
+        }
+    }
+
+    /// Make `tx` the target of `send_protocol`, after sending it the `subscribe` frames and
+    /// the queued protocol frames whose `deadline` hasn't passed.
+    fn go_live(&self, tx: &OutboundSender) -> LiveGuard {
+        let mut live = self.live.lock().unwrap();
+        for frame in self.subscribe_frames() {
+            let _ = tx.send(frame);
+        }
+        let now = now_ms();
+        for frame in self.control_lane.lock().unwrap().drain(..) {
+            if frame.get("deadline").and_then(Value::as_u64).is_none_or(|deadline| deadline > now) {
+                let _ = tx.send(frame);
+            }
+        }
+        *live = Some(tx.clone());
+        LiveGuard(self.live.clone())
+    }
+
+    /// Hand what's buffered to the connection's writer; returns how many frames that was.
+    fn send_pending(&self, tx: &OutboundSender) -> usize {
+        let frames = self.drain_for_socket(Vec::new());
+        let n = frames.len();
+        for ev in frames {
+            let _ = tx.send(ev);
+        }
+        self.unwritten.written();
+        n
+    }
+
+    /// Connected, with nothing buffered, waiting for the writer, or (with acks) unacknowledged.
+    pub(crate) fn is_flushed(&self) -> bool {
+        // In this order: a drain counts itself unwritten before the buffer reads empty, and
+        // tracks events as unacknowledged before its frames are written.
+        let stats = self.stats();
+        stats.connected && stats.buffered == 0 && self.unwritten.is_empty() && self.sync_status().in_flight == 0
+    }
+
+    pub(crate) fn buffer_for_socket(&self, ev: Value) {
+        if !self.spill_to_fallback(&ev) {
+            let evicted = {
+                let ty = event_type(&ev).to_string();
+                let mut buf = self.buffer.lock().unwrap();
+                self.pending_log.append(&ev);
+                buf.push_back(ev);
+                let over_quota = self
+                    .cfg
+                    .buffer_type_limits
+                    .get(&ty)
+                    .is_some_and(|&limit| buf.iter().filter(|e| event_type(e) == ty).count() > limit);
+                let evict = if over_quota {
+                    scheduler::eviction_index(&buf, self.cfg.buffer_ttl_ms, Some(&ty))
+                } else if buf.len() > self.cfg.buffer_limit {
+                    scheduler::eviction_index(&buf, self.cfg.buffer_ttl_ms, None)
+                } else {
+                    None
+                };
+                let evicted = evict.and_then(|i| buf.remove(i));
+                self.pending_log.remove(&evicted);
+                evicted
+            };
+            if let Some(old) = evicted.filter(|old| !self.spill_overflow(old)) {
+                stats::count_drop(&mut self.dropped.lock().unwrap(), &old);
+                self.stats.lock().unwrap().dropped(&old);
+            }
+        }
+        // Also wakes an idle-suspended client so it reconnects.
+        self.wake.notify_one();
+    }
+
+    /// Take everything buffered so far (after `backlog`) in send order, followed by a drop
+    /// notice (with a per-type breakdown) if events were evicted or expired. Counts one
+    /// unwritten frame the caller releases once the result is queued for writing.
+    fn drain_pending(&self, backlog: Vec<Value>) -> Vec<Value> {
+        let mut pending = backlog;
+        {
             let mut buf = self.buffer.lock().unwrap();
-            let pending: Vec<_> = buf.drain(..).collect();
-            let dropped = std::mem::take(&mut *self.dropped.lock().unwrap());
-            (pending, dropped)
-        };
-        for ev in pending {
-            ws.send(Message::Text(ev.to_string().into())).await?;
+            pending.extend(buf.drain(..));
+            self.unwritten.queued(1);
         }
-        if dropped > 0 {
-            let info = json!({"type":"info","level":"info","message":format!("bridge buffered drop count={}", dropped)});
-            ws.send(Message::Text(info.to_string().into())).await?;
+        let expired = scheduler::schedule(&mut pending, self.cfg.buffer_ttl_ms);
+        self.pending_log.remove(&expired);
+        {
+            let clock = self.clock.lock().unwrap();
+            pending.iter_mut().for_each(|ev| clock.adjust(ev, self.cfg.server_timestamps));
+        }
+        let mut dropped = std::mem::take(&mut *self.dropped.lock().unwrap());
+        {
+            let mut stats = self.stats.lock().unwrap();
+            for ev in &expired {
+                stats.dropped(ev);
+                stats::count_drop(&mut dropped, ev);
+            }
+            stats.sent(pending.len());
+        }
+        pending.extend(stats::drop_notice(&dropped));
+        pending
+    }
+
+    /// `drain_pending` for the WebSocket path, where events may need ack tracking and are
+    /// grouped into batch frames when configured.
+    fn drain_for_socket(&self, backlog: Vec<Value>) -> Vec<Value> {
+        let mut pending = self.drain_pending(backlog);
+        if self.tracks_acks() {
+            self.track_unacked(&mut pending);
+        } else {
+            self.pending_log.remove(&pending);
+        }
+        batch::frames(pending, self.batch_config())
+    }
+
+    /// Send what an earlier connection left behind: unacknowledged events, the fallback
+    /// and overflow files, then the buffer. Replayed events keep the `eventId` they got at enqueue time,
+    /// so a host that saw one before the connection dropped can discard the duplicate.
+    async fn flush_buffer(&self, ws: &mut WsStream, compress: Option<usize>) -> Result<(), BridgeError> {
+        let mut backlog = self.take_unacked();
+        backlog.extend(self.take_fallback_events());
+        let frames = self.drain_for_socket(backlog);
+        self.unwritten.queued(frames.len());
+        self.unwritten.written();
+        for ev in frames {
+            self.send_frame(ws, Message::Text(compression::encode(&ev, compress).into())).await?;
+            self.unwritten.written();
+            self.reported_dumps.written(&ev);
         }
         Ok(())
     }
 
-    async fn respond_control(&self, ws: &mut WsStream, msg: &Value) -> Result<(), BridgeError> {
-        let handler_opt = {
-            let guard = self.control_handler.lock().unwrap();
-            guard.clone()
+    /// Run a built-in control action, if one matches and is enabled.
+    fn builtin_control(&self, action: &str, msg: &Value) -> Option<Result<Value, ControlError>> {
+        let args = msg.get("args").unwrap_or(&Value::Null);
+        let outcome = match action {
+            "read_file" => return self.cfg.file_transfer.as_ref().map(|ft| ft.read_file(args)),
+            "write_file" => return self.cfg.file_transfer.as_ref().map(|ft| ft.write_file(args)),
+            "snapshot" | "screenshot" => self.take_snapshot(args),
+            "dump_tasks" => Some(Ok(self.dump_tasks())),
+            #[cfg(feature = "heap-stats")]
+            "heap_stats" => Some(Ok(HeapStats::current().to_json())),
+            #[cfg(feature = "log-collection")]
+            "collect_logs" => self.cfg.log_collection.as_ref().map(|lc| self.collect_logs(lc, args)),
+            "get_env" => self.cfg.env_snapshot.as_ref().map(|env| Ok(env.snapshot(&self.cfg, &self.url()))),
+            _ => None,
         };
-        if let Some(handler) = handler_opt {
-            let id_val = msg.get("id").cloned().unwrap_or(Value::Null);
-            let resp = match handler(msg.clone()) {
-                Ok(res) => json!({"type":"control_result","id":id_val,"ok":true,"result":res}),
-                Err(e) => json!({"type":"control_result","id":id_val,"ok":false,"error":{"message":e}}),
-            };
-            ws.send(Message::Text(resp.to_string().into())).await?;
+        outcome.map(|r| r.map_err(ControlError::from))
+    }
+
+    /// Find the handler for a control request, or `None` when nothing handles it.
+    /// `tx` carries progress updates from async handlers.
+    fn dispatch_control(&self, msg: &Value, tx: Option<&OutboundSender>) -> Option<Dispatch> {
+        let action = msg.get("action").and_then(|a| a.as_str()).unwrap_or("");
+        let args = msg.get("args").unwrap_or(&Value::Null);
+        self.builtin_control(action, msg)
+            .map(Dispatch::Ready)
+            .or_else(|| if action == "eval" { self.run_eval(msg) } else { None })
+            .or_else(|| self.extension_control(action, args).map(Dispatch::Ready))
+            .or_else(|| self.action_control(action, msg, tx))
+            .or_else(|| {
+                let handler = self.control_handler.lock().unwrap().clone()?;
+                Some(Dispatch::Ready(handler(msg.clone())))
+            })
+    }
+
+    /// Answer a control request on the live connection; async handlers run in `inflight`.
+    fn handle_control(&self, req: &ControlRequest, msg: &Value, tx: &OutboundSender, inflight: &mut InFlight) {
+        match self.dispatch_control(msg, Some(tx)) {
+            Some(Dispatch::Ready(outcome)) => {
+                let _ = tx.send(control_response(&req.id, outcome));
+            }
+            Some(Dispatch::Pending(fut)) => {
+                let timeout_ms = req.timeout_ms.or(self.cfg.control_timeout_ms);
+                inflight.start(&req.id, fut, tx.clone(), timeout_ms);
+            }
+            None => {}
         }
+    }
+
+    async fn respond_control(&self, ws: &mut WsStream, req: &ControlRequest, msg: &Value) -> Result<(), BridgeError> {
+        let outcome = match self.dispatch_control(msg, None) {
+            Some(Dispatch::Ready(outcome)) => outcome,
+            Some(Dispatch::Pending(fut)) => match req.timeout_ms.or(self.cfg.control_timeout_ms) {
+                Some(ms) => time::timeout(Duration::from_millis(ms), fut)
+                    .await
+                    .unwrap_or_else(|_| Err(ControlError::timed_out(ms))),
+                None => fut.await,
+            },
+            None => return Ok(()),
+        };
+        self.send_json(ws, &control_response(&req.id, outcome)).await
+    }
+
+    async fn send_json(&self, ws: &mut WsStream, v: &Value) -> Result<(), BridgeError> {
+        self.send_frame(ws, Message::Text(v.to_string().into())).await
+    }
+
+    async fn send_frame(&self, ws: &mut WsStream, msg: Message) -> Result<(), BridgeError> {
+        self.wire.frame(capture::Direction::Sent, &msg);
+        ws.send(msg).await?;
         Ok(())
     }
 
-    async fn wait_for_auth_success(&self, ws: &mut WsStream) -> Result<(), BridgeError> {
+    fn capture_received(&self, msg: Option<&Result<Message, tokio_tungstenite::tungstenite::Error>>) {
+        match msg {
+            Some(Ok(m)) => self.wire.frame(capture::Direction::Received, m),
+            Some(Err(e)) => self.wire.error(e),
+            None => {}
+        }
+    }
+
+    fn set_retry_after(&self, ms: u64) {
+        *self.retry_after.lock().unwrap() = Some(Duration::from_millis(ms));
+    }
+
+    async fn wait_for_auth_success(&self, ws: &mut WsStream) -> Result<AuthSuccess, BridgeError> {
         let deadline = time::Instant::now() + Duration::from_millis(self.cfg.heartbeat_timeout_ms);
         loop {
             let timeout = deadline.saturating_duration_since(time::Instant::now());
@@ -156,20 +1054,38 @@ impl BridgeClient {
                 return Err(BridgeError::AuthTimeout);
             }
             let msg = time::timeout(timeout, ws.next()).await;
+            if let Ok(m) = &msg {
+                self.capture_received(m.as_ref());
+            }
             match msg {
-                Ok(Some(Ok(Message::Text(txt)))) => {
-                    if let Ok(v) = serde_json::from_str::<Value>(&txt) {
-                        match v.get("type").and_then(|t| t.as_str()) {
-                            Some("auth_success") => return Ok(()),
-                            Some("ping") => {
-                                ws.send(Message::Text(json!({"type":"pong"}).to_string().into()))
-                                    .await?;
-                            }
-                            Some("control_request") => {
-                                self.respond_control(ws, &v).await?;
-                            }
-                            _ => {}
-                        }
+                Ok(Some(Ok(Message::Text(txt)))) => match protocol::parse_frame(&txt) {
+                    Some((HostMessage::AuthSuccess(auth), _)) => return Ok(auth),
+                    Some((HostMessage::AuthFailure { reason }, _)) => {
+                        return Err(BridgeError::AuthFailed(reason.unwrap_or_else(|| "rejected".into())));
+                    }
+                    Some((HostMessage::Ping, _)) => self.send_json(ws, &BridgeMessage::Pong.to_json()).await?,
+                    Some((HostMessage::ControlRequest(req), raw)) => self.respond_control(ws, &req, &raw).await?,
+                    Some((HostMessage::Throttle { retry_after_ms }, _)) => self.set_retry_after(retry_after_ms),
+                    Some((
+                        HostMessage::Pong { .. }
+                        | HostMessage::Ack { .. }
+                        | HostMessage::TimeSync { .. }
+                        | HostMessage::ControlCancel { .. }
+                        | HostMessage::BridgeResult(_)
+                        | HostMessage::Event { .. }
+                        | HostMessage::Redirect { .. }
+                        | HostMessage::Unknown,
+                        _,
+                    ))
+                    | None => {}
+                },
+                // Hosts without `auth_failure` reject a bad secret with a policy-violation close.
+                Ok(Some(Ok(Message::Close(Some(frame))))) if frame.code == CloseCode::Policy => {
+                    return Err(BridgeError::AuthFailed(frame.reason.to_string()));
+                }
+                Ok(Some(Ok(Message::Close(Some(frame))))) => {
+                    if let Some(ms) = protocol::close_retry_after(&frame.reason) {
+                        self.set_retry_after(ms);
                     }
                 }
                 Ok(Some(Ok(_))) => {}
@@ -180,117 +1096,444 @@ impl BridgeClient {
         }
     }
 
+    /// Close the connection with a Close frame carrying `code` and `reason`, waiting briefly
+    /// for the host's reply, and make `run_with_reconnect` return `Ok(())`.
+    pub fn close(&self, code: u16, reason: &str) {
+        self.request_close(code, reason, Duration::from_millis(CLOSE_HANDSHAKE_TIMEOUT_MS));
+    }
+
+    /// Stop `run_with_reconnect` cleanly: flush everything buffered, send the `shutdown`
+    /// goodbye event and a normal Close frame, and wait for the loop to exit. Gives up with
+    /// [`BridgeError::ShutdownTimeout`] after `deadline`; returns at once if the loop isn't running.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<(), BridgeError> {
+        let mut state = self.state();
+        self.request_close(CLOSE_NORMAL, "shutdown", deadline);
+        let closed = time::timeout(deadline, state.wait_for(|s| *s == ConnectionState::Closed)).await;
+        closed.map(|_| ()).map_err(|_| BridgeError::ShutdownTimeout)
+    }
+
+    fn request_close(&self, code: u16, reason: &str, drain_timeout: Duration) {
+        *self.close_request.lock().unwrap() = Some(CloseRequest { code, reason: reason.to_string(), drain_timeout });
+        self.close_notify.notify_one();
+    }
+
+    /// Final event before a requested close, so hosts can tell a clean exit from a crash.
+    fn shutdown_event(&self, code: u16, reason: &str) -> Value {
+        let stats = self.stats();
+        let mut ev = json!({
+            "type":"shutdown",
+            "code":code,
+            "reason":reason,
+            "uptimeMs":self.started_at.elapsed().as_millis() as u64,
+            "totals":{"eventsSent":stats.events_sent,"eventsDropped":stats.events_dropped,"connects":stats.connects},
+            "timestamp":now_ms()
+        });
+        self.stamp(&mut ev);
+        ev
+    }
+
+    fn close_requested(&self) -> bool {
+        self.close_request.lock().unwrap().is_some()
+    }
+
     pub async fn run_with_reconnect(&self) -> Result<(), BridgeError> {
-        let mut delay = Duration::from_millis(self.cfg.backoff_initial_ms);
-        loop {
-            match self.connect_once().await {
-                Ok(_) => {
-                    delay = Duration::from_millis(self.cfg.backoff_initial_ms);
+        let result = self.reconnect_loop().await;
+        self.set_state(ConnectionState::Closed);
+        result
+    }
+
+    async fn reconnect_loop(&self) -> Result<(), BridgeError> {
+        let policy = self.backoff_policy();
+        let reset_after = self.cfg.backoff_reset_after_ms.map(Duration::from_millis);
+        let mut attempt = 0u32;
+        let mut failures = 0u32;
+        let mut down_since = None;
+        let mut flaps = 0u32;
+        while !self.close_requested() {
+            let started = time::Instant::now();
+            let connects = self.stats().connects;
+            let outcome = self.connect().await;
+            self.set_connected(false);
+            if self.close_requested() {
+                break;
+            }
+            let retry_after = self.retry_after.lock().unwrap().take();
+            let connected = self.stats().connects > connects;
+            let flapped = connected
+                && outcome.is_err()
+                && self.cfg.flap_breaker.as_ref().is_some_and(|b| started.elapsed() < Duration::from_millis(b.min_session_ms));
+            if reset_after.is_some_and(|d| connected && started.elapsed() >= d) {
+                attempt = 0;
+            }
+            if connected && !flapped {
+                failures = 0;
+                down_since = None;
+                flaps = 0;
+            }
+            match outcome {
+                Err(e @ (BridgeError::AuthFailed(_) | BridgeError::PinMismatch(_))) => return Err(e),
+                Ok(Session::Closed | Session::Redirected) => attempt = 0,
+                Ok(Session::Idle) => {
+                    attempt = 0;
+                    self.set_state(ConnectionState::Idle);
+                    tokio::select! {
+                        _ = self.wake.notified() => {}
+                        _ = self.close_notify.notified() => {}
+                    }
                 }
-                Err(_) => {
-                    let jittered = jitter(delay, self.cfg.backoff_max_ms);
-                    time::sleep(jittered).await;
-                    delay = std::cmp::min(delay * 2, Duration::from_millis(self.cfg.backoff_max_ms));
+                Err(e) => {
+                    let down = *down_since.get_or_insert(if connected && !flapped { time::Instant::now() } else { started });
+                    if !connected || flapped {
+                        failures += 1;
+                        self.rotate_endpoint();
+                    }
+                    let downtime = down.elapsed();
+                    let out_of_attempts = self.cfg.max_reconnect_attempts.is_some_and(|max| failures >= max);
+                    let out_of_time = self.cfg.max_total_downtime_ms.is_some_and(|max| downtime >= Duration::from_millis(max));
+                    if out_of_attempts || out_of_time {
+                        return Err(BridgeError::ReconnectExhausted {
+                            attempts: failures,
+                            downtime_ms: downtime.as_millis() as u64,
+                            last: Box::new(e),
+                        });
+                    }
+                    self.set_state(ConnectionState::Backoff);
+                    let mut wait = retry_after.unwrap_or_else(|| policy.delay(attempt, self.random()));
+                    if flapped {
+                        flaps += 1;
+                        let breaker = self.cfg.flap_breaker.as_ref().unwrap();
+                        if flaps >= breaker.max_flaps {
+                            // Open: sit out the cool-off, then give the endpoint a fresh chance.
+                            wait = wait.max(Duration::from_millis(breaker.cool_off_ms));
+                            flaps = 0;
+                        }
+                    }
+                    tokio::select! {
+                        _ = time::sleep(wait) => {}
+                        _ = self.close_notify.notified() => {}
+                    }
+                    attempt = attempt.saturating_add(1);
                 }
             }
         }
+        Ok(())
+    }
+
+    fn backoff_policy(&self) -> BackoffPolicy {
+        self.cfg.backoff.clone().unwrap_or(BackoffPolicy::Exponential {
+            initial_ms: self.cfg.backoff_initial_ms,
+            max_ms: self.cfg.backoff_max_ms,
+            jitter: 0.5,
+        })
     }
 
-    async fn connect_once(&self) -> Result<(), BridgeError> {
-        let (mut ws, _) = connect_async(&self.cfg.url).await?;
+    async fn connect(&self) -> Result<Session, BridgeError> {
+        self.set_state(ConnectionState::Connecting);
+        // Whatever an earlier connection left unwritten is gone with it.
+        self.unwritten.reset();
+        #[cfg(unix)]
+        let outcome = match local_broker::socket_path(&self.url()) {
+            Some(path) => self.connect_local(&path).await,
+            None => self.connect_once().await,
+        };
+        #[cfg(not(unix))]
+        let outcome = self.connect_once().await;
+        self.notify_disconnect(&outcome);
+        outcome
+    }
 
-        ws.send(Message::Text(
-            json!({"type":"auth","secret":self.cfg.secret,"role":"bridge"}).to_string().into(),
-        ))
-        .await?;
-        self.wait_for_auth_success(&mut ws).await?;
+    async fn connect_once(&self) -> Result<Session, BridgeError> {
+        let mut ws = self.open_connection().await?;
+        self.set_state(ConnectionState::Authenticating);
+        self.clock.lock().unwrap().reset();
+        let auth_sent = now_ms();
+
+        let session_token = self.session_token.lock().unwrap().clone();
+        let auth = BridgeMessage::Auth { secret: self.cfg.secret.clone(), role: "bridge".into(), session_token };
+        self.send_json(&mut ws, &auth.to_json()).await?;
+        let auth = self.wait_for_auth_success(&mut ws).await?;
+        self.clock.lock().unwrap().sample(auth_sent, auth.server_time);
+        let compress = compression::negotiate(self.cfg.compression_threshold_bytes, &auth);
+        // Hosts from before negotiation don't advertise a version and know none of the newer frames.
+        let version = auth.protocol.unwrap_or(1).clamp(1, PROTOCOL_VERSION);
+        self.protocol.store(version, Ordering::Relaxed);
+        // A resumed session keeps its token unless the host issues a new one.
+        if auth.session_token.is_some() || !auth.resumed {
+            *self.session_token.lock().unwrap() = auth.session_token.clone();
+        }
 
-        ws.send(Message::Text(
-            json!({"type":"hello","capabilities":self.cfg.capabilities,"platform":"rust","projectId":self.cfg.project_id,"protocol":PROTOCOL_VERSION}).to_string().into(),
-        ))
-        .await?;
+        if !auth.resumed {
+            let hello = BridgeMessage::Hello {
+                capabilities: self.hello_capabilities(),
+                platform: "rust".into(),
+                project_id: self.cfg.project_id.clone(),
+                protocol: version,
+                metadata: self.hello_metadata(),
+            };
+            self.send_json(&mut ws, &hello.to_json()).await?;
+        }
+        if auth.capabilities.iter().any(|c| c == protocol::TIME_SYNC_CAPABILITY) {
+            self.send_json(&mut ws, &BridgeMessage::TimeSync { client_time: now_ms() }.to_json()).await?;
+        }
 
-        self.flush_buffer(&mut ws).await?;
+        self.set_connected(true);
+        self.set_state(ConnectionState::Connected);
+        self.notify_connect(auth.resumed);
+        self.flush_buffer(&mut ws, compress).await?;
 
         let (mut write, mut read) = ws.split();
-        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-        let tx_clone = tx.clone();
-        let buffer = self.buffer.clone();
-        let dropped = self.dropped.clone();
-        let control_handler = self.control_handler.clone();
+        let (out_tx, mut rx) = mpsc::unbounded_channel::<Outbound>();
+        let tx = OutboundSender { tx: out_tx, unwritten: self.unwritten.clone() };
+        let live = self.go_live(&tx);
+        self.send_pending(&tx);
 
-        {
-            let mut buf = buffer.lock().unwrap();
-            while let Some(ev) = buf.pop_front() {
-                let _ = tx_clone.send(ev);
-            }
-            let dropped_count = std::mem::take(&mut *dropped.lock().unwrap());
-            if dropped_count > 0 {
-                let _ = tx_clone.send(json!({"type":"info","level":"info","message":format!("bridge buffered drop count={}", dropped_count)}));
-            }
-        }
+        let _extension_tasks: Vec<TaskGuard> = self.spawn_extension_tasks().into_iter().map(TaskGuard).collect();
+        #[cfg(feature = "system-metrics")]
+        let _sampler = self
+            .cfg
+            .system_metrics_interval_ms
+            .map(|ms| TaskGuard(system_metrics::spawn(self.clone(), Duration::from_millis(ms))));
+        #[cfg(feature = "heap-stats")]
+        let _heap_reporter = self
+            .cfg
+            .heap_stats_interval_ms
+            .map(|ms| TaskGuard(heap_stats::spawn(self.clone(), Duration::from_millis(ms))));
 
         let heartbeat_interval = Duration::from_millis(self.cfg.heartbeat_interval_ms);
         let heartbeat_timeout = Duration::from_millis(self.cfg.heartbeat_timeout_ms);
         let mut hb_interval = time::interval(heartbeat_interval);
         let mut pong_deadline = time::Instant::now() + heartbeat_timeout;
+        let idle_after = self.cfg.idle_disconnect_ms.map(Duration::from_millis);
+        let mut idle_deadline = idle_after.map(|d| time::Instant::now() + d);
+        let flush_interval = Duration::from_millis(self.cfg.flush_interval_ms);
+        let mut flush_at: Option<time::Instant> = None;
 
-        let sender = tokio::spawn(async move {
-            while let Some(v) = rx.recv().await {
-                let _ = write.send(Message::Text(v.to_string().into())).await;
+        let wire = self.wire.clone();
+        let reported_dumps = self.reported_dumps.clone();
+        let unwritten = self.unwritten.clone();
+        let mut sender = tokio::spawn(async move {
+            while let Some(out) = rx.recv().await {
+                let (msg, frame) = match out {
+                    Outbound::Json(v) => (Message::Text(compression::encode(&v, compress).into()), Some(v)),
+                    Outbound::Close(code, reason) => (
+                        Message::Close(Some(CloseFrame { code: CloseCode::from(code), reason: reason.into() })),
+                        None,
+                    ),
+                };
+                wire.frame(capture::Direction::Sent, &msg);
+                if write.send(msg).await.is_err() {
+                    break;
+                }
+                unwritten.written();
+                if let Some(frame) = frame {
+                    reported_dumps.written(&frame);
+                }
             }
+            // Completes the close handshake (or starts one) before the socket is dropped.
+            let _ = write.close().await;
         });
 
+        let mut closing: Option<time::Instant> = None;
+        let mut inflight = InFlight::default();
+        let mut outcome = Err(BridgeError::Disconnected(DisconnectReason::Error("connection lost".into())));
+        let failback = self.probe_primary();
+        tokio::pin!(failback);
+
         loop {
             tokio::select! {
-                _ = hb_interval.tick() => {
-                    let _ = tx.send(json!({"type":"ping"}));
+                _ = self.close_notify.notified(), if closing.is_none() => {
+                    let request = self.close_request.lock().unwrap().clone();
+                    if let Some(req) = request {
+                        self.flush_metrics();
+                        self.send_pending(&tx);
+                        let _ = tx.send(self.shutdown_event(req.code, &req.reason));
+                        let _ = tx.close(req.code, req.reason);
+                        closing = Some(time::Instant::now() + req.drain_timeout);
+                        outcome = Ok(Session::Closed);
+                    }
+                }
+                _ = time::sleep_until(closing.unwrap_or_else(time::Instant::now)), if closing.is_some() => {
+                    break;
+                }
+                _ = &mut failback, if closing.is_none() => {
+                    let _ = tx.close(CLOSE_NORMAL, "failback".into());
+                    closing = Some(time::Instant::now() + Duration::from_millis(CLOSE_HANDSHAKE_TIMEOUT_MS));
+                    outcome = Ok(Session::Redirected);
+                }
+                _ = self.wake.notified(), if closing.is_none() => {
+                    flush_at.get_or_insert_with(|| time::Instant::now() + flush_interval);
+                }
+                _ = time::sleep_until(flush_at.unwrap_or_else(time::Instant::now)), if flush_at.is_some() && closing.is_none() => {
+                    flush_at = None;
+                    if self.send_pending(&tx) > 0 {
+                        idle_deadline = idle_after.map(|d| time::Instant::now() + d);
+                    }
+                }
+                _ = time::sleep_until(idle_deadline.unwrap_or_else(time::Instant::now)), if idle_deadline.is_some() && closing.is_none() => {
+                    let _ = tx.close(CLOSE_NORMAL, "idle".into());
+                    closing = Some(time::Instant::now() + Duration::from_millis(CLOSE_HANDSHAKE_TIMEOUT_MS));
+                    outcome = Ok(Session::Idle);
+                }
+                _ = hb_interval.tick(), if closing.is_none() => {
+                    self.clock.lock().unwrap().ping_sent(now_ms());
+                    let rtt_ms = if self.cfg.report_rtt { self.stats.lock().unwrap().last_rtt() } else { None };
+                    let _ = tx.send(BridgeMessage::Ping { rtt_ms }.to_json());
                     // do not extend deadline here; only pong extends so timeout can fire
                 }
                 maybe_msg = read.next() => {
+                    self.capture_received(maybe_msg.as_ref());
                     match maybe_msg {
-                        Some(Ok(Message::Text(txt))) => {
-                            if let Ok(v) = serde_json::from_str::<Value>(&txt) {
-                                match v.get("type").and_then(|t| t.as_str()) {
-                                    Some("ping") => { let _ = tx.send(json!({"type":"pong"})); }
-                                    Some("pong") => { pong_deadline = time::Instant::now() + heartbeat_timeout; }
-                                    Some("control_request") => {
-                                        if let Some(handler) = control_handler.lock().unwrap().as_ref() {
-                                            let id_val = v.get("id").cloned().unwrap_or(Value::Null);
-                                            let resp = match handler(v.clone()) {
-                                                Ok(res) => json!({"type":"control_result","id":id_val,"ok":true,"result":res}),
-                                                Err(e) => json!({"type":"control_result","id":id_val,"ok":false,"error":{"message":e}}),
-                                            };
-                                            let _ = tx.send(resp);
-                                        }
-                                    }
-                                    _ => {}
+                        Some(Ok(Message::Text(txt))) => match protocol::parse_frame(&txt) {
+                            Some((HostMessage::Ping, _)) => { let _ = tx.send(BridgeMessage::Pong.to_json()); }
+                            Some((HostMessage::Ack { seq }, _)) => self.handle_ack(seq),
+                            Some((HostMessage::TimeSync { client_time, server_time }, _)) => {
+                                self.clock.lock().unwrap().time_sync(client_time, server_time);
+                            }
+                            Some((HostMessage::Pong { server_time }, _)) => {
+                                pong_deadline = time::Instant::now() + heartbeat_timeout;
+                                let rtt = self.clock.lock().unwrap().pong(server_time);
+                                if let Some(ms) = rtt {
+                                    self.stats.lock().unwrap().rtt(ms);
+                                }
+                            }
+                            Some((HostMessage::ControlRequest(req), raw)) => {
+                                idle_deadline = idle_after.map(|d| time::Instant::now() + d);
+                                self.handle_control(&req, &raw, &tx, &mut inflight);
+                            }
+                            Some((HostMessage::ControlCancel { id }, _)) => inflight.cancel(&id),
+                            Some((HostMessage::BridgeResult(result), _)) => self.handle_bridge_result(result),
+                            Some((HostMessage::Event { topic }, raw)) => self.dispatch_topic_event(&topic, &raw),
+                            Some((HostMessage::Throttle { retry_after_ms }, _)) => self.set_retry_after(retry_after_ms),
+                            Some((HostMessage::Redirect { url }, _)) => {
+                                if closing.is_none() && self.redirect_allowed(&url) {
+                                    *self.url.lock().unwrap() = url;
+                                    let _ = tx.close(CLOSE_NORMAL, "redirect".into());
+                                    closing = Some(time::Instant::now() + Duration::from_millis(CLOSE_HANDSHAKE_TIMEOUT_MS));
+                                    outcome = Ok(Session::Redirected);
                                 }
                             }
+                            Some((HostMessage::AuthSuccess(_) | HostMessage::AuthFailure { .. } | HostMessage::Unknown, _)) | None => {}
+                        },
+                        Some(Ok(Message::Close(frame))) => {
+                            if closing.is_none() {
+                                let (code, reason) = frame.map(|f| (u16::from(f.code), f.reason.to_string())).unwrap_or_default();
+                                if let Some(ms) = protocol::close_retry_after(&reason) {
+                                    self.set_retry_after(ms);
+                                }
+                                outcome = Err(BridgeError::Disconnected(DisconnectReason::ServerClosed { code, reason }));
+                            }
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            if closing.is_none() {
+                                outcome = Err(BridgeError::Disconnected(DisconnectReason::Error(e.to_string())));
+                            }
+                            break;
                         }
-                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        None => break,
                         _ => {}
                     }
                 }
-                _ = time::sleep_until(pong_deadline) => {
+                _ = inflight.reap(), if !inflight.is_empty() => {}
+                _ = time::sleep_until(pong_deadline), if closing.is_none() => {
+                    let _ = tx.close(CLOSE_HEARTBEAT_TIMEOUT, "heartbeat timeout".into());
+                    outcome = Err(BridgeError::Disconnected(DisconnectReason::HeartbeatTimeout));
                     break;
                 }
             }
         }
 
-        sender.abort();
-        Err(BridgeError::AuthTimeout)
+        drop(inflight);
+        // The writer finishes once every sender, including the live one, is gone.
+        drop(live);
+        drop(tx);
+        if time::timeout(Duration::from_millis(CLOSE_HANDSHAKE_TIMEOUT_MS), &mut sender).await.is_err() {
+            sender.abort();
+        }
+        outcome
+    }
+}
+
+fn custom_event<T: Serialize>(event_type: &str, payload: T) -> Result<Value, serde_json::Error> {
+    let mut ev = match serde_json::to_value(payload)? {
+        Value::Object(fields) => Value::Object(fields),
+        Value::Null => json!({}),
+        data => json!({"data": data}),
+    };
+    ev["type"] = event_type.into();
+    ev["timestamp"] = now_ms().into();
+    Ok(ev)
+}
+
+/// Queues frames for the connection's writer task.
+#[derive(Clone)]
+struct OutboundSender {
+    tx: mpsc::UnboundedSender<Outbound>,
+    unwritten: Unwritten,
+}
+
+impl OutboundSender {
+    fn send(&self, v: Value) -> Result<(), mpsc::error::SendError<Outbound>> {
+        self.queue(Outbound::Json(v))
+    }
+
+    fn close(&self, code: u16, reason: String) -> Result<(), mpsc::error::SendError<Outbound>> {
+        self.queue(Outbound::Close(code, reason))
+    }
+
+    fn queue(&self, out: Outbound) -> Result<(), mpsc::error::SendError<Outbound>> {
+        self.unwritten.queued(1);
+        self.tx.send(out).inspect_err(|_| self.unwritten.written())
+    }
+}
+
+/// Frames on their way to the socket, from the moment their events leave the buffer until
+/// they are written.
+#[derive(Clone, Default)]
+struct Unwritten(Arc<AtomicUsize>);
+
+impl Unwritten {
+    fn queued(&self, n: usize) {
+        self.0.fetch_add(n, Ordering::SeqCst);
+    }
+
+    fn written(&self) {
+        let _ = self.0.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+    }
+
+    fn reset(&self) {
+        self.0.store(0, Ordering::SeqCst);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.load(Ordering::SeqCst) == 0
+    }
+}
+
+/// Clears `BridgeClient::live` when the connection goes away.
+struct LiveGuard(Arc<Mutex<Option<OutboundSender>>>);
+
+impl Drop for LiveGuard {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+/// Aborts a background task when the owning connection goes away.
+struct TaskGuard(tokio::task::JoinHandle<()>);
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        self.0.abort();
     }
 }
 
-fn jitter(base: Duration, max_ms: u64) -> Duration {
-    let mut rng = rand::thread_rng();
-    let factor: f64 = rng.gen_range(1.0..=1.5);
-    let dur = base.mul_f64(factor);
-    std::cmp::min(dur, Duration::from_millis(max_ms))
+/// The event's `type`, or `"unknown"`.
+pub(crate) fn event_type(ev: &Value) -> &str {
+    ev.get("type").and_then(|t| t.as_str()).unwrap_or("unknown")
 }
 
-fn now_ms() -> u64 {
+pub(crate) fn now_ms() -> u64 {
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap();
@@ -0,0 +1,103 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{BridgeClient, BridgeError, ConnectionState, Session};
+
+type ConnectHook = Arc<dyn Fn(&ConnectInfo) + Send + Sync>;
+type DisconnectHook = Arc<dyn Fn(&DisconnectReason) + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct Hooks {
+    connect: Vec<ConnectHook>,
+    disconnect: Vec<DisconnectHook>,
+}
+
+/// Passed to [`BridgeClient::on_connect`] once the hello has been sent.
+#[derive(Clone, Debug)]
+pub struct ConnectInfo {
+    pub url: String,
+    /// Successful connections since the client was created, including this one.
+    pub connects: u64,
+    /// The host resumed the previous session instead of starting a new one.
+    pub resumed: bool,
+}
+
+/// Why an established connection ended; passed to [`BridgeClient::on_disconnect`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// No pong arrived within `heartbeat_timeout_ms`.
+    HeartbeatTimeout,
+    /// The host sent a close frame.
+    ServerClosed { code: u16, reason: String },
+    /// [`BridgeClient::close`] or [`BridgeClient::shutdown`] was called.
+    ClientClosed { code: u16, reason: String },
+    /// Closed after `idle_disconnect_ms` without traffic.
+    Idle,
+    /// The host sent a `redirect`, or the primary endpoint is back (see
+    /// [`BridgeConfig::urls`](crate::BridgeConfig::urls)); the client reconnects to `url`.
+    Redirected { url: String },
+    /// The socket failed or ended without a close frame.
+    Error(String),
+}
+
+impl fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HeartbeatTimeout => f.write_str("heartbeat timeout"),
+            Self::ServerClosed { code, reason } => write!(f, "server closed ({} {})", code, reason),
+            Self::ClientClosed { code, reason } => write!(f, "client closed ({} {})", code, reason),
+            Self::Idle => f.write_str("idle"),
+            Self::Redirected { url } => write!(f, "redirected to {}", url),
+            Self::Error(e) => f.write_str(e),
+        }
+    }
+}
+
+impl BridgeClient {
+    /// Called after every successful connect, e.g. to re-register capabilities or log connectivity.
+    pub fn on_connect<F>(&self, hook: F)
+    where
+        F: Fn(&ConnectInfo) + Send + Sync + 'static,
+    {
+        self.hooks.lock().unwrap().connect.push(Arc::new(hook));
+    }
+
+    /// Called when an established connection ends, with the reason.
+    /// Failed connection attempts don't count; watch [`BridgeClient::state`] for those.
+    pub fn on_disconnect<F>(&self, hook: F)
+    where
+        F: Fn(&DisconnectReason) + Send + Sync + 'static,
+    {
+        self.hooks.lock().unwrap().disconnect.push(Arc::new(hook));
+    }
+
+    pub(crate) fn notify_connect(&self, resumed: bool) {
+        let info = ConnectInfo { url: self.url(), connects: self.stats().connects, resumed };
+        let hooks = self.hooks.lock().unwrap().connect.clone();
+        for hook in hooks {
+            hook(&info);
+        }
+    }
+
+    /// Runs the disconnect hooks if `outcome` ended a connected session.
+    pub(crate) fn notify_disconnect(&self, outcome: &Result<Session, BridgeError>) {
+        if *self.state.borrow() != ConnectionState::Connected {
+            return;
+        }
+        let reason = match outcome {
+            Ok(Session::Closed) => {
+                let req = self.close_request.lock().unwrap().clone();
+                let (code, reason) = req.map(|r| (r.code, r.reason)).unwrap_or_default();
+                DisconnectReason::ClientClosed { code, reason }
+            }
+            Ok(Session::Idle) => DisconnectReason::Idle,
+            Ok(Session::Redirected) => DisconnectReason::Redirected { url: self.url() },
+            Err(BridgeError::Disconnected(reason)) => reason.clone(),
+            Err(e) => DisconnectReason::Error(e.to_string()),
+        };
+        let hooks = self.hooks.lock().unwrap().disconnect.clone();
+        for hook in hooks {
+            hook(&reason);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::ErrorKind;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::{BridgeClient, BridgeError, ConnectionState, Session};
+
+/// `unix:///run/aria.sock` → `/run/aria.sock`.
+pub(crate) fn socket_path(url: &str) -> Option<PathBuf> {
+    url.strip_prefix("unix://").map(PathBuf::from)
+}
+
+impl BridgeClient {
+    /// Share this client's host connection with other local processes: clients configured
+    /// with `url: "unix://<path>"` attach here and their events are forwarded through this
+    /// client (its routes and sinks apply). Runs until accepting fails; fails at once with
+    /// `AddrInUse` if another broker is already serving `path`. The socket is made mode
+    /// 0600, and peers running as another user are turned away, so only this user's
+    /// processes can inject events into the authenticated session.
+    pub async fn serve_local(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        // A socket left behind by a broker that exited would make bind fail. Only a socket
+        // nobody is listening on is removed; a live broker's, or any other file, is left alone.
+        if let Err(e) = UnixStream::connect(path).await {
+            let is_socket = std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket());
+            if e.kind() == ErrorKind::ConnectionRefused && is_socket {
+                std::fs::remove_file(path)?;
+            }
+        }
+        let listener = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        // Anyone who connected before the chmod is still caught by the uid check.
+        let owner = std::fs::symlink_metadata(path)?.uid();
+        loop {
+            let (stream, _) = listener.accept().await?;
+            if !stream.peer_cred().is_ok_and(|cred| cred.uid() == owner) {
+                continue;
+            }
+            let client = self.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stream).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if let Ok(ev) = serde_json::from_str::<Value>(&line) {
+                        client.enqueue(ev);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Attached mode: stream buffered events to a local broker as JSON lines. Auth, hello,
+    /// and heartbeats are the broker's job.
+    pub(crate) async fn connect_local(&self, path: &Path) -> Result<Session, BridgeError> {
+        let mut stream = UnixStream::connect(path).await?;
+        let (mut rd, mut wr) = stream.split();
+        self.set_connected(true);
+        self.set_state(ConnectionState::Connected);
+        self.notify_connect(false);
+        let mut backlog = self.take_fallback_events();
+        let mut probe = [0u8; 64];
+        loop {
+            let pending = self.drain_pending(std::mem::take(&mut backlog));
+            self.pending_log.remove(&pending);
+            self.unwritten.queued(pending.len());
+            self.unwritten.written();
+            for ev in pending {
+                let mut line = ev.to_string();
+                line.push('\n');
+                wr.write_all(line.as_bytes()).await?;
+                self.unwritten.written();
+                self.reported_dumps.written(&ev);
+            }
+            if self.close_requested() {
+                let _ = wr.shutdown().await;
+                return Ok(Session::Closed);
+            }
+            tokio::select! {
+                _ = self.wake.notified() => {}
+                _ = self.close_notify.notified() => {}
+                n = rd.read(&mut probe) => {
+                    // The broker never writes; EOF or an error means it went away.
+                    if n? == 0 {
+                        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                    }
+                }
+            }
+        }
+    }
+}
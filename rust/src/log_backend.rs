@@ -0,0 +1,53 @@
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use serde_json::json;
+
+use crate::{now_ms, BridgeClient};
+
+/// Crates on the client's own send path; forwarding their records would make every sent
+/// frame log another event.
+const SELF_TARGETS: &[&str] = &["aria_bridge_client", "tokio_tungstenite", "tungstenite", "rustls"];
+
+/// A `log::Log` backend that forwards records as bridge `console` events
+/// (`{level, message, target}`). Enqueueing never blocks on the socket.
+pub struct BridgeLogger {
+    client: BridgeClient,
+    level: LevelFilter,
+}
+
+impl BridgeLogger {
+    pub fn new(client: BridgeClient, level: LevelFilter) -> Self {
+        Self { client, level }
+    }
+
+    /// Install as the global logger at `Info`; fails if a logger is already set.
+    pub fn init(client: BridgeClient) -> Result<(), SetLoggerError> {
+        Self::init_with_level(client, LevelFilter::Info)
+    }
+
+    pub fn init_with_level(client: BridgeClient, level: LevelFilter) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(Self::new(client, level)))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for BridgeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level && !SELF_TARGETS.iter().any(|t| metadata.target().starts_with(t))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        self.client.enqueue(json!({
+            "type":"console",
+            "level":record.level().as_str().to_ascii_lowercase(),
+            "message":record.args().to_string(),
+            "target":record.target(),
+            "timestamp":now_ms()
+        }));
+    }
+
+    fn flush(&self) {}
+}
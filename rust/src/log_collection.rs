@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+
+pub const LOG_COLLECTION_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+pub const LOG_COLLECTION_MAX_TOTAL_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Directories and limits for the `collect_logs` control action
+/// (requires the `log-collection` feature).
+#[derive(Clone, Debug)]
+pub struct LogCollectionConfig {
+    pub dirs: Vec<PathBuf>,
+    /// File name patterns; `*` matches any run of characters.
+    pub patterns: Vec<String>,
+    pub max_file_bytes: u64,
+    pub max_total_bytes: u64,
+}
+
+impl Default for LogCollectionConfig {
+    fn default() -> Self {
+        Self {
+            dirs: Vec::new(),
+            patterns: vec!["*.log".into()],
+            max_file_bytes: LOG_COLLECTION_MAX_FILE_BYTES,
+            max_total_bytes: LOG_COLLECTION_MAX_TOTAL_BYTES,
+        }
+    }
+}
+
+#[cfg(feature = "log-collection")]
+mod collect {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    use serde_json::{json, Value};
+
+    use super::LogCollectionConfig;
+    use crate::BridgeClient;
+
+    fn glob_match(pattern: &str, name: &str) -> bool {
+        let parts: Vec<&str> = pattern.split('*').collect();
+        if parts.len() == 1 {
+            return pattern == name;
+        }
+        let (first, last) = (parts[0], parts[parts.len() - 1]);
+        if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+            return false;
+        }
+        let mut rest = &name[first.len()..name.len() - last.len()];
+        for part in &parts[1..parts.len() - 1] {
+            match rest.find(part) {
+                Some(idx) => rest = &rest[idx + part.len()..],
+                None => return false,
+            }
+        }
+        true
+    }
+
+    struct Candidate {
+        path: PathBuf,
+        name: String,
+        size: u64,
+        modified: SystemTime,
+    }
+
+    impl LogCollectionConfig {
+        /// Newest matching files first, within the per-file and total caps.
+        fn select(&self, patterns: &[String]) -> (Vec<Candidate>, Vec<Value>) {
+            let mut found = Vec::new();
+            for (idx, dir) in self.dirs.iter().enumerate() {
+                let Ok(entries) = fs::read_dir(dir) else { continue };
+                for entry in entries.flatten() {
+                    let Ok(meta) = entry.metadata() else { continue };
+                    let file_name = entry.file_name().to_string_lossy().into_owned();
+                    if !meta.is_file() || !patterns.iter().any(|p| glob_match(p, &file_name)) {
+                        continue;
+                    }
+                    found.push(Candidate {
+                        path: entry.path(),
+                        // Prefix with the dir index so same-named files from different dirs don't collide.
+                        name: format!("{}/{}", idx, file_name),
+                        size: meta.len(),
+                        modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    });
+                }
+            }
+            found.sort_by_key(|c| std::cmp::Reverse(c.modified));
+            let mut total = 0;
+            let mut picked = Vec::new();
+            let mut skipped = Vec::new();
+            for c in found {
+                if c.size > self.max_file_bytes || total + c.size > self.max_total_bytes {
+                    skipped.push(json!({"file":c.name,"size":c.size}));
+                    continue;
+                }
+                total += c.size;
+                picked.push(c);
+            }
+            (picked, skipped)
+        }
+    }
+
+    fn archive(files: &[Candidate]) -> std::io::Result<Vec<u8>> {
+        let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        for f in files {
+            tar.append_path_with_name(&f.path, &f.name)?;
+        }
+        tar.into_inner()?.finish()
+    }
+
+    impl BridgeClient {
+        /// `collect_logs {patterns?}` → a `.tar.gz` of matching files streamed as `attachment` chunks.
+        pub(crate) fn collect_logs(&self, cfg: &LogCollectionConfig, args: &Value) -> Result<Value, String> {
+            let patterns: Vec<String> = args
+                .get("patterns")
+                .and_then(|p| p.as_array())
+                .map(|p| p.iter().filter_map(|s| s.as_str().map(str::to_string)).collect())
+                .unwrap_or_else(|| cfg.patterns.clone());
+            let (files, skipped) = cfg.select(&patterns);
+            let bytes = archive(&files).map_err(|e| format!("archive failed: {}", e))?;
+            let mut descriptor = self.stream_attachment("logs.tar.gz", "application/gzip", &bytes);
+            descriptor["files"] = files.iter().map(|f| json!({"file":f.name,"size":f.size})).collect();
+            descriptor["skipped"] = Value::Array(skipped);
+            Ok(descriptor)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use futures_util::future::try_join_all;
+use serde_json::Value;
+
+use crate::{BridgeClient, BridgeConfig, BridgeError, ControlError};
+
+/// Runs several [`BridgeClient`]s side by side (e.g. a local dev host plus a shared team
+/// host) and fans every event out to all of them. Each client keeps its own buffer,
+/// heartbeat, and backoff, so one unreachable host doesn't hold up the others.
+#[derive(Clone, Default)]
+pub struct BridgeManager {
+    clients: Vec<BridgeClient>,
+}
+
+impl BridgeManager {
+    pub fn new(configs: Vec<BridgeConfig>) -> Self {
+        Self { clients: configs.into_iter().map(BridgeClient::new).collect() }
+    }
+
+    pub fn add(&mut self, client: BridgeClient) {
+        self.clients.push(client);
+    }
+
+    pub fn clients(&self) -> &[BridgeClient] {
+        &self.clients
+    }
+
+    /// Drive every client's reconnect loop; returns the first error any of them gives up with.
+    pub async fn run_with_reconnect(&self) -> Result<(), BridgeError> {
+        try_join_all(self.clients.iter().map(|c| c.run_with_reconnect())).await?;
+        Ok(())
+    }
+
+    /// Install the same control handler on every client.
+    pub fn on_control<F>(&self, handler: F)
+    where
+        F: Fn(Value) -> Result<Value, ControlError> + Send + Sync + Clone + 'static,
+    {
+        for client in &self.clients {
+            client.on_control(handler.clone());
+        }
+    }
+
+    pub async fn send_console(&self, level: &str, message: &str) {
+        for client in &self.clients {
+            client.send_console(level, message).await;
+        }
+    }
+
+    pub async fn send_error(&self, message: &str) {
+        for client in &self.clients {
+            client.send_error(message).await;
+        }
+    }
+
+    pub async fn mark(&self, name: &str) {
+        for client in &self.clients {
+            client.mark(name).await;
+        }
+    }
+}
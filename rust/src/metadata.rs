@@ -0,0 +1,64 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::BridgeConfig;
+
+/// Identifies the application binary running the bridge. Build it with
+/// [`build_info!`](crate::build_info) so the values come from the calling crate.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub name: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+}
+
+/// Capture the calling crate's name, version, and (if its build script ran
+/// [`build_script::emit_build_info`](crate::build_script::emit_build_info)) git SHA.
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::BuildInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha: option_env!("ARIA_BRIDGE_GIT_SHA").map(|s| s.to_string()),
+        }
+    };
+}
+
+pub(crate) fn process_metadata(cfg: &BridgeConfig) -> Value {
+    let mut meta = json!({
+        "hostname": hostname(),
+        "pid": std::process::id(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "clientVersion": env!("CARGO_PKG_VERSION"),
+        "rustcVersion": env!("ARIA_BRIDGE_RUSTC_VERSION"),
+    });
+    if let Some(build) = &cfg.build_info {
+        meta["build"] = json!(build);
+    }
+    if let Some(version) = &cfg.app_version {
+        meta["appVersion"] = version.as_str().into();
+    }
+    if let Some(obj) = meta.as_object_mut() {
+        for (k, v) in &cfg.extra_metadata {
+            obj.entry(k.as_str()).or_insert_with(|| v.clone());
+        }
+    }
+    meta
+}
+
+fn hostname() -> Option<String> {
+    #[cfg(unix)]
+    for path in ["/proc/sys/kernel/hostname", "/etc/hostname"] {
+        if let Ok(name) = std::fs::read_to_string(path) {
+            let name = name.trim();
+            if !name.is_empty() {
+                return Some(name.to_string());
+            }
+        }
+    }
+    std::env::var("HOSTNAME").or_else(|_| std::env::var("COMPUTERNAME")).ok()
+}
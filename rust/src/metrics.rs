@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Map, Value};
+use tokio::time;
+
+use crate::{now_ms, BridgeClient};
+
+pub const METRIC_WINDOW_MS: u64 = 1_000;
+
+/// How samples of one metric are folded together within a window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum MetricKind {
+    /// `send_metric`: `sum`/`count`/`min`/`max`.
+    Aggregate,
+    /// `send_counter`: increments summed into `value`.
+    Counter,
+    /// `send_gauge`: the latest reading as `value`, plus `min`/`max`.
+    Gauge,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Aggregate => "aggregate",
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+        }
+    }
+}
+
+type MetricKey = (MetricKind, String, Vec<(String, String)>);
+
+struct Aggregate {
+    sum: f64,
+    count: u64,
+    min: f64,
+    max: f64,
+    last: f64,
+}
+
+/// Open aggregation buckets keyed by kind, metric name, and sorted tags.
+#[derive(Default)]
+pub(crate) struct MetricWindow {
+    buckets: BTreeMap<MetricKey, Aggregate>,
+    started_at: u64,
+    flusher: bool,
+}
+
+pub(crate) type MetricState = Arc<Mutex<MetricWindow>>;
+
+fn tags_json(tags: &[(String, String)]) -> Value {
+    Value::Object(tags.iter().map(|(k, v)| (k.clone(), Value::String(v.clone()))).collect::<Map<_, _>>())
+}
+
+impl BridgeClient {
+    /// Record a metric sample. Samples with the same name and tags are folded into one
+    /// `metric` event (`sum`/`count`/`min`/`max`) per `metric_window_ms`; a window of 0
+    /// sends every sample as-is.
+    pub async fn send_metric(&self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.record_metric(MetricKind::Aggregate, name, value, tags);
+    }
+
+    /// Add `value` to a counter; increments within a window go out as one `kind:"counter"`
+    /// event whose `value` is their sum.
+    pub async fn send_counter(&self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.record_metric(MetricKind::Counter, name, value, tags);
+    }
+
+    /// Set a gauge; readings within a window go out as one `kind:"gauge"` event with the
+    /// latest `value` and the window's `min`/`max`.
+    pub async fn send_gauge(&self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        self.record_metric(MetricKind::Gauge, name, value, tags);
+    }
+
+    fn record_metric(&self, kind: MetricKind, name: &str, value: f64, tags: &[(&str, &str)]) {
+        let mut tags: Vec<(String, String)> = tags.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        tags.sort();
+        if self.cfg.metric_window_ms == 0 {
+            let mut ev = json!({
+                "type":"metric",
+                "name":name,
+                "value":value,
+                "tags":tags_json(&tags),
+                "timestamp":now_ms()
+            });
+            if kind != MetricKind::Aggregate {
+                ev["kind"] = kind.as_str().into();
+            }
+            self.enqueue(ev);
+            return;
+        }
+        let spawn_flusher = {
+            let mut window = self.metrics.lock().unwrap();
+            if window.buckets.is_empty() {
+                window.started_at = now_ms();
+            }
+            window
+                .buckets
+                .entry((kind, name.to_string(), tags))
+                .and_modify(|a| {
+                    a.sum += value;
+                    a.count += 1;
+                    a.min = a.min.min(value);
+                    a.max = a.max.max(value);
+                    a.last = value;
+                })
+                .or_insert(Aggregate { sum: value, count: 1, min: value, max: value, last: value });
+            !std::mem::replace(&mut window.flusher, true)
+        };
+        if spawn_flusher {
+            self.spawn_metric_flusher(Duration::from_millis(self.cfg.metric_window_ms));
+        }
+    }
+
+    /// Emit one aggregate event per open bucket and start a new window.
+    pub(crate) fn flush_metrics(&self) {
+        let (buckets, started_at) = {
+            let mut window = self.metrics.lock().unwrap();
+            (std::mem::take(&mut window.buckets), window.started_at)
+        };
+        let now = now_ms();
+        for ((kind, name, tags), agg) in buckets {
+            let mut ev = json!({
+                "type":"metric",
+                "kind":kind.as_str(),
+                "name":name,
+                "tags":tags_json(&tags),
+                "count":agg.count,
+                "windowMs":now.saturating_sub(started_at),
+                "timestamp":now
+            });
+            match kind {
+                MetricKind::Aggregate => {
+                    ev["sum"] = agg.sum.into();
+                    ev["min"] = agg.min.into();
+                    ev["max"] = agg.max.into();
+                }
+                MetricKind::Counter => ev["value"] = agg.sum.into(),
+                MetricKind::Gauge => {
+                    ev["value"] = agg.last.into();
+                    ev["min"] = agg.min.into();
+                    ev["max"] = agg.max.into();
+                }
+            }
+            self.enqueue(ev);
+        }
+    }
+
+    /// Flushes every window; exits once every client handle has been dropped.
+    fn spawn_metric_flusher(&self, every: Duration) {
+        let (client, handles) = self.detach();
+        tokio::spawn(async move {
+            let mut ticker = time::interval(every);
+            ticker.tick().await;
+            while handles.strong_count() > 0 {
+                ticker.tick().await;
+                client.flush_metrics();
+            }
+        });
+    }
+}
@@ -0,0 +1,58 @@
+use std::backtrace::Backtrace;
+use std::panic::{self, PanicHookInfo};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::{debug_meta, now_ms, BridgeClient, ConnectionState};
+
+/// How long a panicking thread waits for the report to be written (and, with `acks`, acknowledged).
+pub const PANIC_FLUSH_TIMEOUT_MS: u64 = 500;
+
+impl BridgeClient {
+    /// Report panics as `error` events (`kind:"panic"`, message, location, thread, backtrace),
+    /// then run the previously installed hook. While connected, the panicking thread waits up
+    /// to [`PANIC_FLUSH_TIMEOUT_MS`] for the event to be sent; this can't help when the panic
+    /// is on the thread driving the client's runtime.
+    pub fn install_panic_hook(&self) {
+        let client = self.clone();
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            client.report_panic(info);
+            previous(info);
+        }));
+    }
+
+    fn report_panic(&self, info: &PanicHookInfo<'_>) {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let location = info.location().map(|l| json!({"file":l.file(),"line":l.line(),"column":l.column()}));
+        self.enqueue(json!({
+            "type":"error",
+            "level":"error",
+            "kind":"panic",
+            "message":format!("panic: {message}"),
+            "location":location,
+            "thread":std::thread::current().name().unwrap_or("<unnamed>"),
+            "backtrace":Backtrace::force_capture().to_string(),
+            "timestamp":now_ms(),
+            "debug":debug_meta::debug_meta(&self.cfg)
+        }));
+        self.flush_blocking(Duration::from_millis(PANIC_FLUSH_TIMEOUT_MS));
+    }
+
+    /// Best effort: block until everything is flushed (see `is_flushed`), or `timeout`.
+    fn flush_blocking(&self, timeout: Duration) {
+        if *self.state.borrow() != ConnectionState::Connected {
+            return;
+        }
+        let deadline = Instant::now() + timeout;
+        while !self.is_flushed() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use serde_json::{json, Value};
+
+use crate::rotating_file::read_lines;
+use crate::BridgeClient;
+
+/// Pending-event logs left by earlier runs are discarded once they are this old.
+pub const PERSIST_MAX_AGE_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Once a log holds this many more lines than twice its live events, it is compacted.
+const COMPACT_SLACK_LINES: usize = 1024;
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    /// `eventId`s of the events in the log that haven't been removed.
+    live: HashSet<String>,
+    /// Lines in the file, tombstones included.
+    lines: usize,
+}
+
+/// Write-ahead log of the in-memory buffer at `<persist_dir>/<project>-<sessionId>.jsonl`:
+/// buffered events are appended, and once one is gone for good (evicted, expired, delivered,
+/// or acknowledged with `acks` on) a `{"removed": eventId}` tombstone is appended after it,
+/// so a crash or restart leaves exactly the undelivered events behind. The file is emptied
+/// when nothing is left and compacted when tombstones pile up. A no-op unless `persist_dir`
+/// is set and the `persistence` feature is enabled.
+#[derive(Clone, Default)]
+pub(crate) struct PendingLog(Option<Arc<Mutex<LogFile>>>);
+
+impl PendingLog {
+    pub(crate) fn open(dir: Option<&Path>, project_id: Option<&str>, session_id: &str) -> Self {
+        let Some(dir) = dir.filter(|_| cfg!(feature = "persistence")) else { return Self::default() };
+        let path = dir.join(format!("{}-{}.jsonl", project_key(project_id), session_id));
+        let file = fs::create_dir_all(dir).and_then(|_| OpenOptions::new().create(true).append(true).open(&path));
+        Self(file.ok().map(|file| Arc::new(Mutex::new(LogFile { path, file, live: HashSet::new(), lines: 0 }))))
+    }
+
+    pub(crate) fn append(&self, ev: &Value) {
+        if let Some(log) = &self.0 {
+            let mut log = log.lock().unwrap();
+            if let Some(id) = event_id(ev) {
+                log.live.insert(id.to_string());
+            }
+            log.write_line(ev);
+        }
+    }
+
+    /// Record that `events` no longer need delivering; events the log doesn't hold are skipped.
+    pub(crate) fn remove<'a>(&self, events: impl IntoIterator<Item = &'a Value>) {
+        let Some(log) = &self.0 else { return };
+        let mut log = log.lock().unwrap();
+        for id in events.into_iter().filter_map(event_id) {
+            if log.live.remove(id) {
+                log.write_line(&json!({ "removed": id }));
+            }
+        }
+        if log.live.is_empty() && log.lines > 0 {
+            // Nothing to lose, so no need for the atomic rewrite below.
+            if log.file.set_len(0).is_ok() {
+                log.lines = 0;
+            }
+        } else if log.lines > 2 * log.live.len() + COMPACT_SLACK_LINES {
+            let _ = log.compact();
+        }
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        self.0.as_ref().map(|log| log.lock().unwrap().path.clone())
+    }
+}
+
+impl LogFile {
+    fn write_line(&mut self, value: &Value) {
+        let mut line = value.to_string();
+        line.push('\n');
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.lines += 1;
+        }
+    }
+
+    /// Rewrite the log without removed events: the live ones go to `<log>.tmp`, which is
+    /// synced and then renamed over the log, so a crash midway leaves one or the other whole.
+    fn compact(&mut self) -> io::Result<()> {
+        let events = replay(&read_lines(&self.path));
+        let mut lines = String::new();
+        for ev in &events {
+            lines.push_str(&ev.to_string());
+            lines.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(lines.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.lines = events.len();
+        Ok(())
+    }
+}
+
+fn event_id(ev: &Value) -> Option<&str> {
+    ev.get("eventId").and_then(Value::as_str)
+}
+
+/// The events a log still holds, in order: those without a later tombstone.
+fn replay(lines: &[String]) -> Vec<Value> {
+    let values: Vec<Value> = lines.iter().filter_map(|l| serde_json::from_str(l).ok()).collect();
+    let is_tombstone = |v: &Value| v.get("type").is_none() && v.get("removed").is_some();
+    let removed: HashSet<&str> = values.iter().filter(|v| is_tombstone(v)).filter_map(|v| v["removed"].as_str()).collect();
+    let mut seen = HashSet::new();
+    values
+        .iter()
+        .filter(|v| !is_tombstone(v))
+        .filter(|v| event_id(v).is_none_or(|id| !removed.contains(id) && seen.insert(id)))
+        .cloned()
+        .collect()
+}
+
+/// Logs are grouped by project so one project's client never replays another's events.
+/// The id is percent-encoded, so distinct ids never share a key (`a.b` is `a%2Eb`, `a_b`
+/// is `a%5Fb`) and the `-` before the session id is unambiguous; no project id is `_`.
+fn project_key(project_id: Option<&str>) -> String {
+    let Some(id) = project_id else { return "_".into() };
+    let mut key = String::new();
+    for b in id.bytes() {
+        if b.is_ascii_alphanumeric() {
+            key.push(b as char);
+        } else {
+            key.push_str(&format!("%{:02X}", b));
+        }
+    }
+    key
+}
+
+impl BridgeClient {
+    /// Re-buffer events that earlier runs of this project left in `persist_dir`, deleting
+    /// their logs; logs older than [`PERSIST_MAX_AGE_MS`] are deleted unread.
+    pub(crate) fn restore_persisted(&self) {
+        let (Some(dir), Some(own)) = (&self.cfg.persist_dir, self.pending_log.path()) else { return };
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        let prefix = format!("{}-", project_key(self.cfg.project_id.as_deref()));
+        let ours: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| p != &own && p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(&prefix)))
+            .collect();
+        // A compaction that was cut short; the log it was replacing is still intact.
+        for tmp in ours.iter().filter(|p| p.extension().is_some_and(|e| e == "tmp")) {
+            let _ = fs::remove_file(tmp);
+        }
+        let mut logs: Vec<(SystemTime, PathBuf)> = ours
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "jsonl"))
+            .filter_map(|p| Some((fs::metadata(&p).and_then(|m| m.modified()).ok()?, p)))
+            .collect();
+        logs.sort();
+        for (modified, path) in logs {
+            let stale = modified.elapsed().unwrap_or_default() > Duration::from_millis(PERSIST_MAX_AGE_MS);
+            if !stale {
+                for mut ev in replay(&read_lines(&path)) {
+                    // Unacknowledged events carry the old run's `seq`; they are numbered afresh.
+                    if let Some(obj) = ev.as_object_mut() {
+                        obj.remove("seq");
+                    }
+                    self.buffer_for_socket(ev);
+                }
+            }
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
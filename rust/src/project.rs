@@ -0,0 +1,57 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{custom_event, debug_meta, now_ms, BridgeClient, BridgeError};
+
+/// A view of a [`BridgeClient`] that tags every event with `projectId`, for agents serving
+/// several tenants over one connection. Cheap to clone; shares the client's buffer.
+#[derive(Clone)]
+pub struct ProjectHandle {
+    client: BridgeClient,
+    project_id: String,
+}
+
+impl ProjectHandle {
+    pub fn project_id(&self) -> &str {
+        &self.project_id
+    }
+
+    pub async fn send_console(&self, level: &str, message: &str) {
+        self.client.send_console_for(&self.project_id, level, message).await;
+    }
+
+    pub async fn send_error(&self, message: &str) {
+        self.client.send_error_for(&self.project_id, message).await;
+    }
+
+    /// [`BridgeClient::send_event`] with this handle's `projectId`.
+    pub async fn send_event<T: Serialize>(&self, event_type: &str, payload: T) -> Result<(), BridgeError> {
+        let mut ev = custom_event(event_type, payload)?;
+        ev["projectId"] = Value::from(self.project_id.as_str());
+        self.client.enqueue(ev);
+        Ok(())
+    }
+}
+
+impl BridgeClient {
+    /// Scoped handle whose events carry `project_id` instead of the config's project.
+    pub fn with_project(&self, project_id: &str) -> ProjectHandle {
+        ProjectHandle { client: self.clone(), project_id: project_id.to_string() }
+    }
+
+    pub async fn send_console_for(&self, project_id: &str, level: &str, message: &str) {
+        let ev = json!({"type":"console","level":level,"message":message,"projectId":project_id,"timestamp":now_ms()});
+        self.enqueue(ev);
+    }
+
+    pub async fn send_error_for(&self, project_id: &str, message: &str) {
+        let ev = json!({
+            "type":"error",
+            "message":message,
+            "projectId":project_id,
+            "timestamp":now_ms(),
+            "debug":debug_meta::debug_meta(&self.cfg)
+        });
+        self.enqueue(ev);
+    }
+}
@@ -0,0 +1,207 @@
+//! Typed protocol frames exchanged with the host, for handling them exhaustively here and for
+//! building hosts against. Events stay free-form JSON; these are the messages around them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ControlError;
+
+/// Listed in `auth_success.capabilities` by hosts that answer `time_sync`.
+pub const TIME_SYNC_CAPABILITY: &str = "time_sync";
+
+/// Frames a host sends to a bridge.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostMessage {
+    AuthSuccess(AuthSuccess),
+    AuthFailure {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    Ping,
+    Pong {
+        #[serde(default, rename = "serverTime", skip_serializing_if = "Option::is_none")]
+        server_time: Option<u64>,
+    },
+    Ack {
+        seq: u64,
+    },
+    /// Reply to [`BridgeMessage::TimeSync`], echoing its `clientTime`.
+    TimeSync {
+        #[serde(rename = "clientTime")]
+        client_time: u64,
+        #[serde(rename = "serverTime")]
+        server_time: u64,
+    },
+    ControlRequest(ControlRequest),
+    ControlCancel {
+        id: String,
+    },
+    BridgeResult(BridgeResult),
+    /// A broadcast on a topic the bridge subscribed to; the rest of the frame is the payload.
+    Event {
+        topic: String,
+    },
+    /// Move to another endpoint: close and reconnect to `url` from now on.
+    Redirect {
+        url: String,
+    },
+    /// The host is overloaded: wait `retryAfterMs` before the next reconnect.
+    Throttle {
+        #[serde(rename = "retryAfterMs", alias = "retry_after_ms")]
+        retry_after_ms: u64,
+    },
+    /// Any `type` this client doesn't know; ignored.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthSuccess {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Encodings the host can decode; see the `compression` feature.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compression: Vec<String>,
+    /// Optional requests the host answers, such as [`TIME_SYNC_CAPABILITY`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<String>,
+    /// Host clock in epoch ms, for clock sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_time: Option<u64>,
+    /// Highest protocol version the host speaks; absent means version 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<u64>,
+    /// Opaque token the client presents in later `auth` frames to resume this session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+    /// The host picked up the session named by the `auth` frame's token; no `hello` follows.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resumed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControlRequest {
+    pub id: String,
+    pub action: String,
+    #[serde(default)]
+    pub args: Value,
+    #[serde(default, rename = "timeoutMs", alias = "timeout_ms", skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Top-level `eval` source, as the JS host sends it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BridgeResult {
+    pub id: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub result: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ControlError>,
+}
+
+/// Protocol frames a bridge sends to its host (events are sent as plain JSON objects).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeMessage {
+    Auth {
+        secret: String,
+        role: String,
+        /// The `sessionToken` from the last `auth_success`, when reconnecting.
+        #[serde(rename = "sessionToken", default, skip_serializing_if = "Option::is_none")]
+        session_token: Option<String>,
+    },
+    Hello {
+        capabilities: Vec<String>,
+        platform: String,
+        #[serde(rename = "projectId")]
+        project_id: Option<String>,
+        protocol: u64,
+        metadata: Value,
+    },
+    /// Ask for the host's broadcasts on `topic`; see [`HostMessage::Event`].
+    Subscribe {
+        topic: String,
+    },
+    /// The full capability list after [`add_capability`](crate::BridgeClient::add_capability)
+    /// or `remove_capability` on a live connection.
+    CapabilitiesUpdate {
+        capabilities: Vec<String>,
+    },
+    Ping {
+        /// Latest measured round trip, when `report_rtt` is set.
+        #[serde(default, rename = "rttMs", skip_serializing_if = "Option::is_none")]
+        rtt_ms: Option<u64>,
+    },
+    Pong,
+    /// Asks the host for its clock right after hello, if it advertised
+    /// [`TIME_SYNC_CAPABILITY`]; see [`HostMessage::TimeSync`].
+    TimeSync {
+        #[serde(rename = "clientTime")]
+        client_time: u64,
+    },
+    ControlResult {
+        id: String,
+        ok: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<ControlError>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        cancelled: bool,
+    },
+    ControlProgress {
+        id: String,
+        progress: Value,
+        timestamp: u64,
+    },
+    BridgeRequest {
+        id: String,
+        action: String,
+        args: Value,
+        timestamp: u64,
+        deadline: u64,
+    },
+}
+
+impl HostMessage {
+    /// `Err` for a frame with a known `type` but malformed fields.
+    pub fn from_json(v: &Value) -> Result<Self, serde_json::Error> {
+        Self::deserialize(v)
+    }
+}
+
+/// Parse a text frame, keeping the raw JSON for handlers that take it (`on_control`).
+/// Non-JSON and malformed frames give `None` and are ignored.
+pub(crate) fn parse_frame(txt: &str) -> Option<(HostMessage, Value)> {
+    let raw: Value = serde_json::from_str(txt).ok()?;
+    let msg = HostMessage::from_json(&raw).ok()?;
+    Some((msg, raw))
+}
+
+/// A `retryAfterMs` (or `retry_after_ms`) hint in a close frame's JSON reason.
+pub(crate) fn close_retry_after(reason: &str) -> Option<u64> {
+    let v: Value = serde_json::from_str(reason).ok()?;
+    v.get("retryAfterMs").or_else(|| v.get("retry_after_ms"))?.as_u64()
+}
+
+impl BridgeMessage {
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).expect("protocol frames always serialize")
+    }
+
+    /// The `control_result` for request `id`.
+    pub(crate) fn control_result(id: &str, outcome: Result<Value, ControlError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(res) => (Some(res), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self::ControlResult { id: id.to_string(), ok: error.is_none(), result, error, cancelled: false }
+    }
+}
@@ -0,0 +1,82 @@
+//! Python bindings (feature `python`): the `aria_bridge_native` extension module wraps a
+//! client running on its own thread, so Python code gets the same reconnect, heartbeat, and
+//! buffering behaviour without an event loop.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use serde_json::Value;
+
+use crate::blocking;
+use crate::{BridgeConfig, ControlError};
+
+/// `BridgeClient(url, secret, project_id=None)`: connects immediately in the background.
+#[pyclass(name = "BridgeClient", module = "aria_bridge_native")]
+struct PyBridgeClient {
+    bridge: Mutex<Option<blocking::BridgeClient>>,
+}
+
+fn runtime_error(e: impl ToString) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+impl PyBridgeClient {
+    fn with_client<T>(&self, f: impl FnOnce(&blocking::BridgeClient) -> T) -> PyResult<T> {
+        let bridge = self.bridge.lock().unwrap();
+        let bridge = bridge.as_ref().ok_or_else(|| runtime_error("bridge is shut down"))?;
+        Ok(f(bridge))
+    }
+}
+
+#[pymethods]
+impl PyBridgeClient {
+    #[new]
+    #[pyo3(signature = (url, secret, project_id=None))]
+    fn new(url: String, secret: String, project_id: Option<String>) -> PyResult<Self> {
+        let bridge = blocking::BridgeClient::new(BridgeConfig { url, secret, project_id, ..Default::default() }).map_err(runtime_error)?;
+        Ok(Self { bridge: Mutex::new(Some(bridge)) })
+    }
+
+    fn send_console(&self, level: &str, message: &str) -> PyResult<()> {
+        self.with_client(|client| client.send_console(level, message))
+    }
+
+    fn send_error(&self, message: &str) -> PyResult<()> {
+        self.with_client(|client| client.send_error(message))
+    }
+
+    /// Answer control requests with `handler(request: dict)`; its return value must be
+    /// JSON-serializable, and an exception fails the request with its message.
+    fn on_control(&self, handler: PyObject) -> PyResult<()> {
+        self.with_client(|client| {
+            client.on_control(move |msg| Python::with_gil(|py| call_handler(py, &handler, &msg)).map_err(ControlError::from));
+        })
+    }
+
+    /// Flush, send the goodbye event, and close, waiting up to `timeout_ms`. Further sends raise.
+    #[pyo3(signature = (timeout_ms=2000))]
+    fn shutdown(&self, py: Python<'_>, timeout_ms: u64) -> PyResult<()> {
+        let Some(bridge) = self.bridge.lock().unwrap().take() else { return Ok(()) };
+        py.allow_threads(|| bridge.shutdown(Duration::from_millis(timeout_ms)).map_err(|e| e.to_string()))
+            .map_err(runtime_error)
+    }
+}
+
+/// Round-trips through the `json` module so handlers see plain dicts and lists.
+fn call_handler(py: Python<'_>, handler: &PyObject, msg: &Value) -> Result<Value, String> {
+    let run = || -> PyResult<String> {
+        let json = py.import("json")?;
+        let request = json.call_method1("loads", (msg.to_string(),))?;
+        let result = handler.call1(py, (request,))?;
+        json.call_method1("dumps", (result,))?.extract()
+    };
+    let text = run().map_err(|e| e.to_string())?;
+    serde_json::from_str(&text).map_err(|e| e.to_string())
+}
+
+#[pymodule]
+fn aria_bridge_native(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBridgeClient>()
+}
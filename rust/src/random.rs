@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use crate::BridgeClient;
+
+/// Returns uniform values in `[0, 1)`; used for reconnect jitter. See
+/// [`BridgeClient::set_random_source`].
+pub type RandomSource = Arc<dyn Fn() -> f64 + Send + Sync>;
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// splitmix64 over a process-wide counter seeded from the clock and pid. Not for
+/// cryptography; just enough spread for jitter and ids without pulling in `rand`.
+pub(crate) fn next_u64() -> u64 {
+    static STATE: OnceLock<AtomicU64> = OnceLock::new();
+    let state = STATE.get_or_init(|| {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        AtomicU64::new(nanos ^ ((std::process::id() as u64) << 32))
+    });
+    let mut z = state.fetch_add(GOLDEN_GAMMA, Ordering::Relaxed).wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub(crate) fn next_f64() -> f64 {
+    (next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl BridgeClient {
+    /// Replace the built-in generator used for reconnect jitter, e.g. with a platform RNG
+    /// or a fixed value in tests.
+    pub fn set_random_source<F>(&self, source: F)
+    where
+        F: Fn() -> f64 + Send + Sync + 'static,
+    {
+        *self.random.lock().unwrap() = Some(Arc::new(source));
+    }
+
+    pub(crate) fn random(&self) -> f64 {
+        match self.random.lock().unwrap().as_ref() {
+            Some(source) => source(),
+            None => next_f64(),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Append-only JSONL file that rolls over to `name.1.jsonl`, `name.2.jsonl`... once it
+/// exceeds `max_bytes`, keeping at most `max_files` rotated files.
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: Option<File>,
+    written: u64,
+    /// Lines from files deleted by rotation, when tracked (see `tracking_discarded`).
+    discarded: Option<Vec<String>>,
+}
+
+impl RotatingFile {
+    pub(crate) fn new(path: PathBuf, max_bytes: u64, max_files: usize) -> Self {
+        Self { path, max_bytes, max_files, file: None, written: 0, discarded: None }
+    }
+
+    /// Keep the lines rotation deletes so the caller can account for them; see `take_discarded`.
+    pub(crate) fn tracking_discarded(mut self) -> Self {
+        self.discarded = Some(Vec::new());
+        self
+    }
+
+    pub(crate) fn take_discarded(&mut self) -> Vec<String> {
+        self.discarded.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn discard(&mut self, path: &Path) -> io::Result<()> {
+        if let Some(discarded) = &mut self.discarded {
+            discarded.extend(read_lines(path));
+        }
+        fs::remove_file(path)
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let ext = self.path.extension().map(|e| e.to_string_lossy().into_owned()).unwrap_or_else(|| "jsonl".into());
+        self.path.with_file_name(format!("{}.{}.{}", stem, n, ext))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        let oldest = self.rotated(self.max_files);
+        if oldest.exists() {
+            let _ = self.discard(&oldest);
+        }
+        for n in (1..self.max_files).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                fs::rename(from, self.rotated(n + 1))?;
+            }
+        }
+        if self.max_files > 0 {
+            fs::rename(&self.path, self.rotated(1))?;
+        } else {
+            let path = self.path.clone();
+            self.discard(&path)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn append_line(&mut self, line: &str) -> io::Result<()> {
+        if self.file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            self.written = file.metadata()?.len();
+            self.file = Some(file);
+        }
+        if self.written > 0 && self.written + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate()?;
+            return self.append_line(line);
+        }
+        let file = self.file.as_mut().expect("opened above");
+        file.write_all(line.as_bytes())?;
+        file.write_all(b"\n")?;
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Every file in the set, oldest first.
+    pub(crate) fn files(&self) -> Vec<PathBuf> {
+        let mut out: Vec<PathBuf> = (1..=self.max_files).rev().map(|n| self.rotated(n)).filter(|p| p.exists()).collect();
+        if self.path.exists() {
+            out.push(self.path.clone());
+        }
+        out
+    }
+
+    /// Read back all lines (oldest first) and delete the files.
+    pub(crate) fn drain_lines(&mut self) -> Vec<String> {
+        self.file = None;
+        self.written = 0;
+        let mut lines = Vec::new();
+        for path in self.files() {
+            lines.extend(read_lines(&path));
+            let _ = fs::remove_file(&path);
+        }
+        lines
+    }
+}
+
+/// Lines that aren't valid UTF-8 are skipped rather than ending the read.
+pub(crate) fn read_lines(path: &Path) -> Vec<String> {
+    match File::open(path) {
+        Ok(f) => BufReader::new(f)
+            .split(b'\n')
+            .map_while(Result::ok)
+            .filter_map(|l| String::from_utf8(l).ok())
+            .filter(|l| !l.is_empty())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
@@ -0,0 +1,44 @@
+use serde_json::Value;
+
+/// What a matching [`RouteRule`] does with an event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RouteAction {
+    #[default]
+    Send,
+    Drop,
+}
+
+/// Declarative filter deciding whether an event goes to this client's host. Rules in
+/// `BridgeConfig::routes` are checked in order and the first match wins; events matching
+/// no rule are sent. Empty lists match anything.
+///
+/// With a [`BridgeManager`](crate::BridgeManager), give each host its own rules, e.g. drop
+/// `debug`/`log`/`info` console events on the shared host but keep them on the local one.
+#[derive(Clone, Debug, Default)]
+pub struct RouteRule {
+    pub types: Vec<String>,
+    pub levels: Vec<String>,
+    /// `(key, value)` pairs that must all appear in the event's `tags` object.
+    pub tags: Vec<(String, String)>,
+    pub action: RouteAction,
+}
+
+impl RouteRule {
+    fn matches(&self, ev: &Value) -> bool {
+        let ty = ev.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        let level = ev
+            .get("level")
+            .and_then(|l| l.as_str())
+            .unwrap_or(if ty == "error" { "error" } else { "info" });
+        (self.types.is_empty() || self.types.iter().any(|t| t == ty))
+            && (self.levels.is_empty() || self.levels.iter().any(|l| l == level))
+            && self
+                .tags
+                .iter()
+                .all(|(k, v)| ev.get("tags").and_then(|t| t.get(k)).and_then(|t| t.as_str()) == Some(v.as_str()))
+    }
+}
+
+pub(crate) fn should_send(rules: &[RouteRule], ev: &Value) -> bool {
+    rules.iter().find(|r| r.matches(ev)).map(|r| r.action) != Some(RouteAction::Drop)
+}
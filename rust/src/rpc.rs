@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tokio::time;
+
+use crate::protocol::{BridgeMessage, BridgeResult};
+use crate::{now_ms, BridgeClient, BridgeError, ControlError};
+
+pub const REQUEST_TIMEOUT_MS: u64 = 30_000;
+
+/// `bridge_request`s waiting for the host's `bridge_result`, by id.
+#[derive(Default)]
+pub(crate) struct RpcState {
+    next_id: u64,
+    pending: HashMap<String, oneshot::Sender<BridgeResult>>,
+}
+
+impl BridgeClient {
+    /// Ask the host: sends `bridge_request {id, action, args}` and resolves with the `result`
+    /// of the matching `bridge_result`. Waits up to `request_timeout_ms`, across reconnects.
+    pub async fn request(&self, action: &str, args: Value) -> Result<Value, BridgeError> {
+        self.request_with_timeout(action, args, Duration::from_millis(self.cfg.request_timeout_ms)).await
+    }
+
+    /// [`BridgeClient::request`] with its own timeout.
+    pub async fn request_with_timeout(&self, action: &str, args: Value, timeout: Duration) -> Result<Value, BridgeError> {
+        let (tx, rx) = oneshot::channel();
+        let id = {
+            let mut rpc = self.rpc.lock().unwrap();
+            rpc.next_id += 1;
+            let id = format!("req-{}", rpc.next_id);
+            rpc.pending.insert(id.clone(), tx);
+            id
+        };
+        let now = now_ms();
+        // The deadline keeps a request that outlived its caller from being sent after a reconnect.
+        self.send_protocol(
+            BridgeMessage::BridgeRequest {
+                id: id.clone(),
+                action: action.to_string(),
+                args,
+                timestamp: now,
+                deadline: now + timeout.as_millis() as u64,
+            }
+            .to_json(),
+            true,
+        );
+        let reply = time::timeout(timeout, rx).await;
+        self.rpc.lock().unwrap().pending.remove(&id);
+        let Ok(Ok(reply)) = reply else {
+            return Err(BridgeError::RequestTimeout);
+        };
+        if reply.ok {
+            return Ok(reply.result);
+        }
+        let error = reply.error.unwrap_or_else(|| ControlError::from("request failed"));
+        Err(BridgeError::Request(error))
+    }
+
+    pub(crate) fn handle_bridge_result(&self, result: BridgeResult) {
+        if let Some(tx) = self.rpc.lock().unwrap().pending.remove(&result.id) {
+            let _ = tx.send(result);
+        }
+    }
+}
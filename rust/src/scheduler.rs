@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{event_type, now_ms, BridgeClient};
+
+/// Send order for buffered events. Higher priorities leave the buffer first and are
+/// evicted last; set it per event with [`BridgeClient::send_with`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Priority {
+    fn as_str(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+
+    /// The event's `priority` field, or a default from its type: errors are high,
+    /// `debug`/`trace` console lines are low.
+    pub(crate) fn of(ev: &Value) -> Priority {
+        match ev.get("priority").and_then(|p| p.as_str()) {
+            Some("high") => return Priority::High,
+            Some("normal") => return Priority::Normal,
+            Some("low") => return Priority::Low,
+            _ => {}
+        }
+        let ty = ev.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        let level = ev.get("level").and_then(|l| l.as_str()).unwrap_or("");
+        match (ty, level) {
+            ("error", _) | (_, "error") => Priority::High,
+            (_, "debug") | (_, "trace") => Priority::Low,
+            _ => Priority::Normal,
+        }
+    }
+}
+
+/// Per-event delivery hints for [`BridgeClient::send_with`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SendOptions {
+    pub priority: Option<Priority>,
+    /// Drop the event if it is still buffered this long after being sent (high priority
+    /// events are kept regardless).
+    pub deadline: Option<Duration>,
+}
+
+/// Events past their `deadline` (epoch ms), or older than `ttl_ms`, are stale unless
+/// they are high priority.
+fn expired(ev: &Value, now: u64, ttl_ms: Option<u64>) -> bool {
+    let past_deadline = ev.get("deadline").and_then(|d| d.as_u64()).is_some_and(|d| d < now);
+    // A replayed event whose `timestamp` was moved onto the host clock keeps its local one.
+    let local = ev.get("localTimestamp").or_else(|| ev.get("timestamp"));
+    let too_old = ttl_ms
+        .zip(local.and_then(|t| t.as_u64()))
+        .is_some_and(|(ttl, ts)| now.saturating_sub(ts) > ttl);
+    (past_deadline || too_old) && Priority::of(ev) < Priority::High
+}
+
+/// Index to evict from a full buffer (or from the events of type `ty`, when that type is over
+/// its quota): the first stale event, else the oldest of the lowest priority.
+pub(crate) fn eviction_index(buf: &VecDeque<Value>, ttl_ms: Option<u64>, ty: Option<&str>) -> Option<usize> {
+    let now = now_ms();
+    let candidate = |ev: &Value| ty.is_none_or(|ty| event_type(ev) == ty);
+    buf.iter().position(|ev| candidate(ev) && expired(ev, now, ttl_ms)).or_else(|| {
+        let lowest = buf.iter().filter(|ev| candidate(ev)).map(Priority::of).min()?;
+        buf.iter().position(|ev| candidate(ev) && Priority::of(ev) == lowest)
+    })
+}
+
+/// Order a backlog for sending: stale events are removed and the rest are sorted by priority,
+/// keeping arrival order within each priority. Returns the events removed.
+pub(crate) fn schedule(events: &mut Vec<Value>, ttl_ms: Option<u64>) -> Vec<Value> {
+    let now = now_ms();
+    let (stale, mut keep): (Vec<Value>, Vec<Value>) = events.drain(..).partition(|ev| expired(ev, now, ttl_ms));
+    keep.sort_by_key(|ev| std::cmp::Reverse(Priority::of(ev)));
+    *events = keep;
+    stale
+}
+
+impl BridgeClient {
+    /// Send an arbitrary event with an explicit priority and/or deadline. The options are
+    /// recorded on the event as `priority` and `deadline` (epoch ms).
+    pub async fn send_with(&self, mut ev: Value, opts: SendOptions) {
+        if let Some(obj) = ev.as_object_mut() {
+            if let Some(priority) = opts.priority {
+                obj.insert("priority".into(), priority.as_str().into());
+            }
+            if let Some(deadline) = opts.deadline {
+                obj.insert("deadline".into(), (now_ms() + deadline.as_millis() as u64).into());
+            }
+            obj.entry("timestamp").or_insert_with(|| now_ms().into());
+        }
+        self.enqueue(ev);
+    }
+}
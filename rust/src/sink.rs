@@ -0,0 +1,167 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::{self, BoxFuture};
+use futures_util::FutureExt;
+use serde_json::Value;
+use tokio::runtime::{Builder, Handle};
+use tokio::sync::mpsc;
+
+use crate::rotating_file::RotatingFile;
+use crate::BridgeClient;
+
+/// An asynchronous destination for outgoing events. Every event that passes the client's
+/// pipeline (routing rules) is handed to each sink registered with
+/// [`BridgeClient::add_sink`] at once, and their deliveries run concurrently; the
+/// WebSocket connection is the client's own, default sink. Each sink sees events in order:
+/// the next event's deliveries start once the previous one's have all finished.
+///
+/// Every [`EventSink`] is a `Sink` whose delivery completes on the spot.
+pub trait Sink: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Deliver `event`. The future must not borrow the sink; clone what it needs (an
+    /// `Arc`'d client or channel sender, say) into it.
+    fn deliver(&self, event: &Value) -> BoxFuture<'static, Result<(), String>>;
+}
+
+/// A synchronous destination for outgoing events; see [`Sink`].
+pub trait EventSink: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn send(&self, event: &Value) -> Result<(), String>;
+
+    fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<T: EventSink + ?Sized> Sink for T {
+    fn name(&self) -> &str {
+        EventSink::name(self)
+    }
+
+    fn deliver(&self, event: &Value) -> BoxFuture<'static, Result<(), String>> {
+        future::ready(self.send(event)).boxed()
+    }
+}
+
+type Delivery = BoxFuture<'static, ()>;
+
+#[derive(Default)]
+struct Dispatcher {
+    tx: Option<mpsc::UnboundedSender<Delivery>>,
+    /// Events whose deliveries are queued or running.
+    queued: Arc<AtomicUsize>,
+}
+
+/// Runs the deliveries of sinks that don't finish on the spot, one event at a time, on the
+/// runtime of whoever first needed it (or a thread of its own when there is none).
+#[derive(Clone, Default)]
+pub(crate) struct Deliveries(Arc<Mutex<Dispatcher>>);
+
+impl Deliveries {
+    /// Start the deliveries of one event, after those of earlier events have finished.
+    pub(crate) fn start(&self, deliveries: Vec<BoxFuture<'static, Result<(), String>>>) {
+        let mut dispatcher = self.0.lock().unwrap();
+        // With nothing queued they can start right here, and most (every `EventSink`) finish.
+        let pending: Vec<_> = if dispatcher.queued.load(Ordering::SeqCst) == 0 {
+            deliveries.into_iter().filter_map(|mut d| d.as_mut().now_or_never().is_none().then_some(d)).collect()
+        } else {
+            deliveries
+        };
+        if pending.is_empty() {
+            return;
+        }
+        // The runtime it ran on may have shut down since, taking what was queued with it.
+        if dispatcher.tx.as_ref().is_none_or(|tx| tx.is_closed()) {
+            dispatcher.queued = Arc::new(AtomicUsize::new(0));
+            dispatcher.tx = Some(spawn_dispatcher(dispatcher.queued.clone()));
+        }
+        dispatcher.queued.fetch_add(1, Ordering::SeqCst);
+        let _ = dispatcher.tx.as_ref().unwrap().send(future::join_all(pending).map(drop).boxed());
+    }
+}
+
+fn spawn_dispatcher(queued: Arc<AtomicUsize>) -> mpsc::UnboundedSender<Delivery> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Delivery>();
+    let run = async move {
+        while let Some(delivery) = rx.recv().await {
+            delivery.await;
+            queued.fetch_sub(1, Ordering::SeqCst);
+        }
+    };
+    match Handle::try_current() {
+        Ok(handle) => drop(handle.spawn(run)),
+        Err(_) => {
+            let _ = std::thread::Builder::new().name("aria-bridge-sinks".into()).spawn(move || {
+                if let Ok(rt) = Builder::new_current_thread().enable_all().build() {
+                    rt.block_on(run);
+                }
+            });
+        }
+    }
+    tx
+}
+
+impl EventSink for BridgeClient {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    /// Buffer for the host connection (or the offline fallback file).
+    fn send(&self, event: &Value) -> Result<(), String> {
+        self.buffer_for_socket(event.clone());
+        Ok(())
+    }
+}
+
+/// Appends each event as one JSON line, optionally rotating by size.
+pub struct FileSink {
+    file: Mutex<RotatingFile>,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::rotating(path, u64::MAX, 0)
+    }
+
+    pub fn rotating(path: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> Self {
+        Self { file: Mutex::new(RotatingFile::new(path.into(), max_bytes, max_files)) }
+    }
+}
+
+impl EventSink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn send(&self, event: &Value) -> Result<(), String> {
+        self.file.lock().unwrap().append_line(&event.to_string()).map_err(|e| e.to_string())
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        self.file.lock().unwrap().flush().map_err(|e| e.to_string())
+    }
+}
+
+/// Writes each event as one JSON line to stdout.
+#[derive(Default)]
+pub struct StdoutSink;
+
+impl EventSink for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    fn send(&self, event: &Value) -> Result<(), String> {
+        let mut out = std::io::stdout().lock();
+        writeln!(out, "{}", event).map_err(|e| e.to_string())
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        std::io::stdout().flush().map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,59 @@
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{debug_meta, now_ms, BridgeClient};
+
+/// Where an event was emitted from; build it with [`source_location!`](crate::source_location).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct SourceLocation {
+    pub file: &'static str,
+    pub line: u32,
+    pub module: &'static str,
+}
+
+impl BridgeClient {
+    /// [`send_console`](Self::send_console) with a `location {file, line, module}` the host
+    /// can link to; usually called through [`bridge_console!`](crate::bridge_console).
+    pub async fn send_console_at(&self, level: &str, message: &str, location: SourceLocation) {
+        let ev = json!({"type":"console","level":level,"message":message,"location":location,"timestamp":now_ms()});
+        self.enqueue(ev);
+    }
+
+    /// [`send_error`](Self::send_error) with a source location; see [`bridge_error!`](crate::bridge_error).
+    pub async fn send_error_at(&self, message: &str, location: SourceLocation) {
+        let ev = json!({
+            "type":"error",
+            "message":message,
+            "location":location,
+            "timestamp":now_ms(),
+            "debug":debug_meta::debug_meta(&self.cfg)
+        });
+        self.enqueue(ev);
+    }
+}
+
+/// The [`SourceLocation`] of the macro call.
+#[macro_export]
+macro_rules! source_location {
+    () => {
+        $crate::SourceLocation { file: file!(), line: line!(), module: module_path!() }
+    };
+}
+
+/// `bridge_console!(client, "info", "loaded {} rows", n).await`: a console event carrying the
+/// caller's file, line, and module.
+#[macro_export]
+macro_rules! bridge_console {
+    ($client:expr, $level:expr, $($arg:tt)+) => {
+        $client.send_console_at($level, &format!($($arg)+), $crate::source_location!())
+    };
+}
+
+/// `bridge_error!(client, "query failed: {}", err).await`: an error event carrying the caller's
+/// file, line, and module.
+#[macro_export]
+macro_rules! bridge_error {
+    ($client:expr, $($arg:tt)+) => {
+        $client.send_error_at(&format!($($arg)+), $crate::source_location!())
+    };
+}
@@ -0,0 +1,31 @@
+use tokio::sync::watch;
+
+use crate::BridgeClient;
+
+/// Where the reconnect loop is; watch it via [`BridgeClient::state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Opening the socket.
+    Connecting,
+    /// Socket open, waiting for `auth_success`.
+    Authenticating,
+    /// Hello sent; events are flowing.
+    Connected,
+    /// Waiting out the reconnect delay after a failure.
+    Backoff,
+    /// Disconnected for inactivity (`idle_disconnect_ms`); the next event reconnects.
+    Idle,
+    /// `run_with_reconnect` isn't running (not started yet, or closed).
+    Closed,
+}
+
+impl BridgeClient {
+    /// Subscribe to connection state changes, e.g. to drive a status indicator.
+    pub fn state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    pub(crate) fn set_state(&self, state: ConnectionState) {
+        self.state.send_if_modified(|current| std::mem::replace(current, state) != state);
+    }
+}
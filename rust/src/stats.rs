@@ -0,0 +1,111 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{event_type, now_ms, BridgeClient};
+
+/// Ping round trips averaged into [`BridgeStats::avg_rtt_ms`].
+pub const RTT_WINDOW: usize = 10;
+
+/// Running counters behind [`BridgeClient::stats`].
+#[derive(Default)]
+pub(crate) struct Counters {
+    connected_since: Option<u64>,
+    connects: u64,
+    events_sent: u64,
+    events_dropped: u64,
+    dropped_by_type: BTreeMap<String, u64>,
+    rtts: VecDeque<u64>,
+}
+
+impl Counters {
+    pub(crate) fn set_connected(&mut self, connected: bool) {
+        if !connected {
+            self.connected_since = None;
+        } else if self.connected_since.is_none() {
+            self.connected_since = Some(now_ms());
+            self.connects += 1;
+        }
+    }
+
+    pub(crate) fn sent(&mut self, n: usize) {
+        self.events_sent += n as u64;
+    }
+
+    pub(crate) fn dropped(&mut self, ev: &Value) {
+        self.events_dropped += 1;
+        *self.dropped_by_type.entry(event_type(ev).to_string()).or_default() += 1;
+    }
+
+    pub(crate) fn rtt(&mut self, ms: u64) {
+        if self.rtts.len() == RTT_WINDOW {
+            self.rtts.pop_front();
+        }
+        self.rtts.push_back(ms);
+    }
+
+    pub(crate) fn last_rtt(&self) -> Option<u64> {
+        self.rtts.back().copied()
+    }
+}
+
+/// Point-in-time view of the client's connection and buffer.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeStats {
+    pub connected: bool,
+    /// Epoch ms when the current connection was established.
+    pub connected_since: Option<u64>,
+    /// Successful connections since the client was created.
+    pub connects: u64,
+    /// Events waiting in the in-memory buffer.
+    pub buffered: usize,
+    /// Events handed to the socket.
+    pub events_sent: u64,
+    /// Events evicted from a full buffer or expired before sending.
+    pub events_dropped: u64,
+    /// `events_dropped` broken down by event `type`.
+    pub dropped_by_type: BTreeMap<String, u64>,
+    /// Round trip of the latest answered heartbeat ping.
+    pub last_rtt_ms: Option<u64>,
+    /// Mean round trip over the last [`RTT_WINDOW`] pings.
+    pub avg_rtt_ms: Option<f64>,
+}
+
+impl BridgeClient {
+    pub fn stats(&self) -> BridgeStats {
+        let buffered = self.buffer.lock().unwrap().len();
+        let c = self.stats.lock().unwrap();
+        BridgeStats {
+            connected: c.connected_since.is_some(),
+            connected_since: c.connected_since,
+            connects: c.connects,
+            buffered,
+            events_sent: c.events_sent,
+            events_dropped: c.events_dropped,
+            dropped_by_type: c.dropped_by_type.clone(),
+            last_rtt_ms: c.last_rtt(),
+            avg_rtt_ms: (!c.rtts.is_empty()).then(|| c.rtts.iter().sum::<u64>() as f64 / c.rtts.len() as f64),
+        }
+    }
+}
+
+/// Events dropped since the last drop notice, by `type`.
+pub(crate) type DropCounts = BTreeMap<String, usize>;
+
+pub(crate) fn count_drop(counts: &mut DropCounts, ev: &Value) {
+    *counts.entry(event_type(ev).to_string()).or_default() += 1;
+}
+
+/// `{type:"info", message:"bridge buffered drop count=42 (console: 40, error: 2)", dropped}`,
+/// or `None` when nothing was dropped.
+pub(crate) fn drop_notice(counts: &DropCounts) -> Option<Value> {
+    let total: usize = counts.values().sum();
+    if total == 0 {
+        return None;
+    }
+    let breakdown: Vec<String> = counts.iter().map(|(ty, n)| format!("{}: {}", ty, n)).collect();
+    let message = format!("bridge buffered drop count={} ({})", total, breakdown.join(", "));
+    Some(json!({"type":"info","level":"info","message":message,"dropped":counts}))
+}
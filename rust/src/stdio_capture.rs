@@ -0,0 +1,93 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::fd::{AsRawFd, FromRawFd, RawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::json;
+
+use crate::{now_ms, routing, BridgeClient};
+
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+impl BridgeClient {
+    /// Redirect this process's stdout and stderr through pipes and forward each line as a
+    /// `console` event (`level` `info`/`warn`, `stream` `stdout`/`stderr`). Lines are still
+    /// written to the original streams. Captured lines skip [`add_sink`](Self::add_sink) sinks,
+    /// so a [`StdoutSink`](crate::StdoutSink) can't feed itself. Once per process, and not
+    /// while the client talks over `stdio://`, whose frames own stdout. If either stream
+    /// can't be redirected, both are left as they were.
+    pub fn capture_stdio(&self) -> io::Result<()> {
+        if self.url().starts_with("stdio://") || self.endpoints().iter().any(|url| url.starts_with("stdio://")) {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "stdout carries the stdio:// transport"));
+        }
+        if CAPTURING.swap(true, Ordering::SeqCst) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "stdio is already captured"));
+        }
+        let captured = self.redirect_stdio();
+        if captured.is_err() {
+            CAPTURING.store(false, Ordering::SeqCst);
+        }
+        captured
+    }
+
+    /// Everything that can fail before the streams change (pipes, reader threads) happens
+    /// first; a reader whose pipe is never installed just sees EOF and exits.
+    fn redirect_stdio(&self) -> io::Result<()> {
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+        let (out_original, out_pipe) = self.forward_fd(libc::STDOUT_FILENO, "stdout", "info")?;
+        let (_, err_pipe) = self.forward_fd(libc::STDERR_FILENO, "stderr", "warn")?;
+        // SAFETY: dup2 onto the standard fds; `out_original` stays open in its reader thread
+        // until the pipe that replaced stdout is closed, which only this rollback does.
+        unsafe {
+            check(libc::dup2(out_pipe.as_raw_fd(), libc::STDOUT_FILENO))?;
+            if let Err(e) = check(libc::dup2(err_pipe.as_raw_fd(), libc::STDERR_FILENO)) {
+                libc::dup2(out_original, libc::STDOUT_FILENO);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a thread that copies lines from a new pipe to a duplicate of `fd` and forwards
+    /// them; returns that duplicate's fd and the pipe's write end, to be installed over `fd`.
+    fn forward_fd(&self, fd: RawFd, stream: &'static str, level: &'static str) -> io::Result<(RawFd, File)> {
+        let mut pipe: [RawFd; 2] = [0; 2];
+        // SAFETY: plain fd syscalls; every fd created here is owned by exactly one File below.
+        let original = unsafe { File::from_raw_fd(check(libc::dup(fd))?) };
+        let (reader, writer) = unsafe {
+            check(libc::pipe(pipe.as_mut_ptr()))?;
+            (File::from_raw_fd(pipe[0]), File::from_raw_fd(pipe[1]))
+        };
+        let original_fd = original.as_raw_fd();
+        let client = self.clone();
+        std::thread::Builder::new().name(format!("aria-bridge-{stream}")).spawn(move || {
+            let mut original = original;
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            while reader.read_until(b'\n', &mut line).unwrap_or(0) > 0 {
+                let _ = original.write_all(&line);
+                let text = String::from_utf8_lossy(&line);
+                client.forward_captured(stream, level, text.trim_end_matches(['\n', '\r']));
+                line.clear();
+            }
+        })?;
+        Ok((original_fd, writer))
+    }
+
+    fn forward_captured(&self, stream: &str, level: &str, message: &str) {
+        let mut ev = json!({"type":"console","level":level,"message":message,"stream":stream,"timestamp":now_ms()});
+        self.stamp(&mut ev);
+        if routing::should_send(&self.cfg.routes, &ev) {
+            self.buffer_for_socket(ev);
+        }
+    }
+}
+
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
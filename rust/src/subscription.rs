@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::protocol::BridgeMessage;
+use crate::BridgeClient;
+
+type TopicHandler = Arc<dyn Fn(&Value) + Send + Sync>;
+
+/// Handlers per subscribed topic, in registration order.
+pub(crate) type Subscriptions = BTreeMap<String, Vec<TopicHandler>>;
+
+impl BridgeClient {
+    /// Receive the host's broadcasts on `topic`: sends `subscribe {topic}` (again after every
+    /// reconnect) and calls `handler` with each `{type:"event", topic, ...}` frame for it.
+    /// Handlers run on the connection task, so keep them quick.
+    pub fn subscribe<F>(&self, topic: &str, handler: F)
+    where
+        F: Fn(&Value) + Send + Sync + 'static,
+    {
+        // Holding `live` keeps a connection from starting between the registration and the
+        // send, which would subscribe twice or not at all.
+        let live = self.live.lock().unwrap();
+        let first = {
+            let mut subs = self.subscriptions.lock().unwrap();
+            let handlers = subs.entry(topic.to_string()).or_default();
+            handlers.push(Arc::new(handler));
+            handlers.len() == 1
+        };
+        if let (true, Some(tx)) = (first, live.as_ref()) {
+            let _ = tx.send(BridgeMessage::Subscribe { topic: topic.to_string() }.to_json());
+        }
+    }
+
+    /// `subscribe` frames for every topic, sent when a connection comes up.
+    pub(crate) fn subscribe_frames(&self) -> Vec<Value> {
+        let subs = self.subscriptions.lock().unwrap();
+        subs.keys().map(|topic| BridgeMessage::Subscribe { topic: topic.clone() }.to_json()).collect()
+    }
+
+    pub(crate) fn dispatch_topic_event(&self, topic: &str, frame: &Value) {
+        let handlers = self.subscriptions.lock().unwrap().get(topic).cloned().unwrap_or_default();
+        for handler in handlers {
+            handler(frame);
+        }
+    }
+}
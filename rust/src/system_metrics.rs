@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use crate::{now_ms, BridgeClient};
+
+/// Samples the current process via sysinfo and turns each reading into a `metric` event.
+pub(crate) struct Sampler {
+    sys: System,
+    pid: Pid,
+}
+
+impl Sampler {
+    pub(crate) fn new() -> Self {
+        Self { sys: System::new(), pid: Pid::from_u32(std::process::id()) }
+    }
+
+    pub(crate) fn sample(&mut self) -> Vec<Value> {
+        let kind = ProcessRefreshKind::nothing().with_cpu().with_memory().with_tasks();
+        self.sys.refresh_processes_specifics(ProcessesToUpdate::Some(&[self.pid]), true, kind);
+        let Some(proc_) = self.sys.process(self.pid) else {
+            return Vec::new();
+        };
+        let ts = now_ms();
+        let mut out = vec![
+            metric("process.cpu_percent", proc_.cpu_usage() as f64, "percent", ts),
+            metric("process.rss_bytes", proc_.memory() as f64, "bytes", ts),
+        ];
+        if let Some(fds) = proc_.open_files() {
+            out.push(metric("process.open_fds", fds as f64, "count", ts));
+        }
+        if let Some(tasks) = proc_.tasks() {
+            out.push(metric("process.threads", tasks.len() as f64, "count", ts));
+        }
+        out
+    }
+}
+
+fn metric(name: &str, value: f64, unit: &str, ts: u64) -> Value {
+    json!({"type":"metric","kind":"gauge","name":name,"value":value,"unit":unit,"timestamp":ts})
+}
+
+/// Spawn the periodic sampler; events go through the client's normal buffer.
+pub(crate) fn spawn(client: BridgeClient, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut sampler = Sampler::new();
+        let mut ticker = time::interval(every);
+        loop {
+            ticker.tick().await;
+            for ev in sampler.sample() {
+                client.enqueue(ev);
+            }
+        }
+    })
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde_json::{json, Value};
+
+use crate::BridgeClient;
+
+pub(crate) type TaskRegistry = Arc<Mutex<HashMap<u64, (String, Instant)>>>;
+
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Keeps a named unit of work listed in `dump_tasks` until dropped.
+pub struct TrackedTask {
+    id: u64,
+    registry: TaskRegistry,
+}
+
+impl Drop for TrackedTask {
+    fn drop(&mut self) {
+        self.registry.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl BridgeClient {
+    /// Register an outstanding task (request, job, background loop) so `dump_tasks`
+    /// reports it with its running time; hold the guard for as long as the work runs.
+    pub fn track_task(&self, name: &str) -> TrackedTask {
+        let id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
+        self.tasks.lock().unwrap().insert(id, (name.to_string(), Instant::now()));
+        TrackedTask { id, registry: self.tasks.clone() }
+    }
+
+    /// `dump_tasks` → OS threads, tokio runtime counters, and tracked tasks.
+    pub(crate) fn dump_tasks(&self) -> Value {
+        let mut tracked: Vec<Value> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, (name, started))| json!({"id":id,"name":name,"elapsedMs":started.elapsed().as_millis() as u64}))
+            .collect();
+        tracked.sort_by_key(|t| std::cmp::Reverse(t["elapsedMs"].as_u64()));
+        let runtime = tokio::runtime::Handle::try_current().ok().map(|h| {
+            let m = h.metrics();
+            json!({"workers":m.num_workers(),"aliveTasks":m.num_alive_tasks(),"globalQueueDepth":m.global_queue_depth()})
+        });
+        json!({"threads":threads(),"runtime":runtime,"tasks":tracked})
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn threads() -> Vec<Value> {
+    let Ok(entries) = std::fs::read_dir("/proc/self/task") else {
+        return Vec::new();
+    };
+    let mut out: Vec<Value> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let tid: u64 = entry.file_name().to_str()?.parse().ok()?;
+            let name = std::fs::read_to_string(entry.path().join("comm")).ok()?;
+            // stat is "tid (comm) S ..."; the state follows the last ')'.
+            let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+            let state = stat.rsplit_once(')').and_then(|(_, rest)| rest.split_whitespace().next()).unwrap_or("?");
+            Some(json!({"tid":tid,"name":name.trim(),"state":thread_state(state)}))
+        })
+        .collect();
+    out.sort_by_key(|t| t["tid"].as_u64());
+    out
+}
+
+#[cfg(target_os = "linux")]
+fn thread_state(code: &str) -> &'static str {
+    match code {
+        "R" => "running",
+        "S" => "sleeping",
+        "D" => "blocked",
+        "T" | "t" => "stopped",
+        "Z" => "zombie",
+        _ => "unknown",
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn threads() -> Vec<Value> {
+    let current = std::thread::current();
+    vec![json!({"name":current.name().unwrap_or("<unnamed>"),"state":"running"})]
+}
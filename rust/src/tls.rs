@@ -0,0 +1,182 @@
+//! TLS settings for `wss://` connections (feature `rustls`): a private CA, a client certificate
+//! for mutual TLS, a ready-made rustls `ClientConfig`, and public-key pins. With a
+//! [`TlsConfig`] the client runs the TLS handshake itself, so pins are checked before the
+//! WebSocket upgrade sends anything.
+
+use std::fmt;
+use std::sync::Arc;
+
+use base64::Engine;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+
+use crate::BridgeError;
+
+/// See [`BridgeConfig::tls`](crate::BridgeConfig::tls). Without one, `wss://` uses the OS
+/// root store and no client certificate.
+#[derive(Clone)]
+pub struct TlsConfig {
+    /// PEM CA certificates to trust, e.g. an internal CA.
+    pub ca_pem: Option<Vec<u8>>,
+    /// Also trust the OS root store (default); turn off to trust only `ca_pem`.
+    pub native_roots: bool,
+    /// PEM certificate chain and private key presented to hosts that ask for one.
+    pub client_cert_pem: Option<Vec<u8>>,
+    pub client_key_pem: Option<Vec<u8>>,
+    /// Use this config as-is; the fields above are ignored.
+    pub client_config: Option<Arc<ClientConfig>>,
+    /// Base64 SHA-256 hashes of trusted SubjectPublicKeyInfos (`sha256/` prefix optional).
+    /// When set, some certificate the host presents must match one, on top of normal
+    /// verification; otherwise the connection fails with [`BridgeError::PinMismatch`].
+    pub spki_pins: Vec<String>,
+    /// Skip certificate verification entirely (feature `danger-insecure-tls`), for
+    /// self-signed development hosts such as `wss://localhost`. Anyone on the path can
+    /// impersonate the host; never enable it in production. `spki_pins` still apply.
+    #[cfg(feature = "danger-insecure-tls")]
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            ca_pem: None,
+            native_roots: true,
+            client_cert_pem: None,
+            client_key_pem: None,
+            client_config: None,
+            spki_pins: Vec::new(),
+            #[cfg(feature = "danger-insecure-tls")]
+            danger_accept_invalid_certs: false,
+        }
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("TlsConfig");
+        f.field("ca_pem", &self.ca_pem.as_ref().map(|pem| pem.len()))
+            .field("native_roots", &self.native_roots)
+            .field("client_cert_pem", &self.client_cert_pem.as_ref().map(|pem| pem.len()))
+            .field("client_key_pem", &self.client_key_pem.as_ref().map(|_| crate::REDACTED))
+            .field("client_config", &self.client_config.is_some())
+            .field("spki_pins", &self.spki_pins);
+        #[cfg(feature = "danger-insecure-tls")]
+        f.field("danger_accept_invalid_certs", &self.danger_accept_invalid_certs);
+        f.finish()
+    }
+}
+
+impl TlsConfig {
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn client_config(&self) -> Result<Arc<ClientConfig>, BridgeError> {
+        if let Some(config) = &self.client_config {
+            return Ok(config.clone());
+        }
+        let mut roots = RootCertStore::empty();
+        if self.native_roots {
+            roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+        }
+        if let Some(pem) = &self.ca_pem {
+            for cert in CertificateDer::pem_slice_iter(pem) {
+                roots.add(cert.map_err(tls_error)?).map_err(tls_error)?;
+            }
+        }
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions().map_err(tls_error)?;
+        #[cfg(feature = "danger-insecure-tls")]
+        let builder = if self.danger_accept_invalid_certs {
+            builder.dangerous().with_custom_certificate_verifier(Arc::new(insecure::AcceptAnyCert(provider)))
+        } else {
+            builder.with_root_certificates(roots)
+        };
+        #[cfg(not(feature = "danger-insecure-tls"))]
+        let builder = builder.with_root_certificates(roots);
+        let config = match (&self.client_cert_pem, &self.client_key_pem) {
+            (Some(cert), Some(key)) => {
+                let chain = CertificateDer::pem_slice_iter(cert).collect::<Result<Vec<_>, _>>().map_err(tls_error)?;
+                let key = PrivateKeyDer::from_pem_slice(key).map_err(tls_error)?;
+                builder.with_client_auth_cert(chain, key).map_err(tls_error)?
+            }
+            (None, None) => builder.with_no_client_auth(),
+            _ => return Err(BridgeError::Tls("client_cert_pem and client_key_pem go together".into())),
+        };
+        Ok(Arc::new(config))
+    }
+
+    /// Built before dialing so configuration errors surface without a connection attempt.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn connector(&self) -> Result<TlsConnector, BridgeError> {
+        Ok(TlsConnector::from(self.client_config()?))
+    }
+
+    /// TLS handshake with `host` over `stream`, then the pin check.
+    pub(crate) async fn connect(&self, connector: &TlsConnector, host: &str, stream: TcpStream) -> Result<TlsStream<TcpStream>, BridgeError> {
+        let name = ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']').to_string()).map_err(tls_error)?;
+        let stream = connector.connect(name, stream).await?;
+        if !self.spki_pins.is_empty() {
+            let chain = stream.get_ref().1.peer_certificates().unwrap_or_default();
+            let hashes: Vec<String> = chain.iter().filter_map(spki_sha256).collect();
+            let pinned = |hash: &String| self.spki_pins.iter().any(|pin| pin.strip_prefix("sha256/").unwrap_or(pin) == hash);
+            if !hashes.iter().any(pinned) {
+                return Err(BridgeError::PinMismatch(hashes.into_iter().next().unwrap_or_default()));
+            }
+        }
+        Ok(stream)
+    }
+}
+
+/// Base64 SHA-256 of the certificate's DER-encoded SubjectPublicKeyInfo, the value pins hold
+/// (`openssl x509 -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`).
+fn spki_sha256(cert: &CertificateDer<'_>) -> Option<String> {
+    let cert = webpki::EndEntityCert::try_from(cert).ok()?;
+    let digest = ring::digest::digest(&ring::digest::SHA256, cert.subject_public_key_info().as_ref());
+    Some(base64::engine::general_purpose::STANDARD.encode(digest))
+}
+
+fn tls_error(e: impl fmt::Display) -> BridgeError {
+    BridgeError::Tls(e.to_string())
+}
+
+#[cfg(feature = "danger-insecure-tls")]
+mod insecure {
+    use std::sync::Arc;
+
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+    use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use rustls::{DigitallySignedStruct, Error, SignatureScheme};
+
+    /// Accepts any certificate for any name; handshake signatures are still checked so the
+    /// host has to hold the key for the certificate it sends.
+    #[derive(Debug)]
+    pub(super) struct AcceptAnyCert(pub(super) Arc<CryptoProvider>);
+
+    impl ServerCertVerifier for AcceptAnyCert {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+        }
+
+        fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+}
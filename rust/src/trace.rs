@@ -0,0 +1,70 @@
+use std::time::Instant;
+
+use serde_json::json;
+
+use crate::{now_ms, random, BridgeClient};
+
+/// An open span; emits `{type:"trace", phase:"end", durationMs}` when ended or dropped.
+pub struct SpanGuard {
+    client: BridgeClient,
+    span_id: String,
+    name: String,
+    started: Instant,
+    ended: bool,
+}
+
+impl SpanGuard {
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    /// Start a span nested under this one (`parentSpanId` is set).
+    pub fn child(&self, name: &str) -> SpanGuard {
+        self.client.open_span(name, Some(&self.span_id))
+    }
+
+    /// End the span now; returns its duration in milliseconds.
+    pub fn end(mut self) -> f64 {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> f64 {
+        self.ended = true;
+        let duration = self.started.elapsed().as_secs_f64() * 1000.0;
+        self.client.enqueue(json!({
+            "type":"trace",
+            "phase":"end",
+            "spanId":self.span_id,
+            "name":self.name,
+            "durationMs":duration,
+            "timestamp":now_ms()
+        }));
+        duration
+    }
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.finish();
+        }
+    }
+}
+
+impl BridgeClient {
+    /// Start a performance span: emits `{type:"trace", phase:"start", spanId, name}` now and
+    /// the matching `end` event when the returned guard is ended or dropped.
+    pub fn start_span(&self, name: &str) -> SpanGuard {
+        self.open_span(name, None)
+    }
+
+    fn open_span(&self, name: &str, parent: Option<&str>) -> SpanGuard {
+        let span_id = format!("{:016x}", random::next_u64());
+        let mut ev = json!({"type":"trace","phase":"start","spanId":span_id,"name":name,"timestamp":now_ms()});
+        if let Some(parent) = parent {
+            ev["parentSpanId"] = parent.into();
+        }
+        self.enqueue(ev);
+        SpanGuard { client: self.clone(), span_id, name: name.to_string(), started: Instant::now(), ended: false }
+    }
+}
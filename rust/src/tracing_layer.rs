@@ -0,0 +1,96 @@
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::{now_ms, BridgeClient};
+
+/// A `tracing_subscriber` layer that forwards events as bridge `console` events; build it
+/// with [`tracing_layer`].
+#[derive(Clone)]
+pub struct BridgeLayer {
+    client: BridgeClient,
+}
+
+/// Forward `tracing` events to `client` as `{type:"console", level, message, target, fields}`.
+/// Add it to your subscriber: `tracing_subscriber::registry().with(tracing_layer(client.clone()))`.
+pub fn tracing_layer(client: BridgeClient) -> BridgeLayer {
+    BridgeLayer { client }
+}
+
+fn level_name(level: &Level) -> &'static str {
+    match *level {
+        Level::TRACE => "trace",
+        Level::DEBUG => "debug",
+        Level::INFO => "info",
+        Level::WARN => "warn",
+        Level::ERROR => "error",
+    }
+}
+
+/// Collects an event's `message` and the rest of its fields as JSON.
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Map<String, Value>,
+}
+
+impl FieldVisitor {
+    fn record(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            });
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, json!(format!("{value:?}")));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for BridgeLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let mut ev = json!({
+            "type":"console",
+            "level":level_name(meta.level()),
+            "message":visitor.message.unwrap_or_default(),
+            "target":meta.target(),
+            "timestamp":now_ms()
+        });
+        if !visitor.fields.is_empty() {
+            ev["fields"] = Value::Object(visitor.fields);
+        }
+        self.client.enqueue(ev);
+    }
+}
@@ -0,0 +1,187 @@
+//! Pluggable connections. The client speaks the protocol over any [`Connection`], a duplex
+//! stream of WebSocket-style [`Message`]s; a [`Transport`] opens one for a URL. By default
+//! `tcp://` URLs use [`tcp::TcpTransport`], `stdio://` `stdio::StdioTransport` (with the
+//! `stdio-transport` feature), and
+//! everything else [`WebSocketTransport`]; swap it with [`BridgeClient::set_transport`] for custom TLS
+//! stacks, tunnels, or test doubles while keeping auth, heartbeats, and buffering;
+//! [`memory::pair`] is a ready-made double.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_util::future::BoxFuture;
+use futures_util::{sink, stream, Sink, Stream};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::{self, HeaderName, HeaderValue};
+pub use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+use crate::{BridgeClient, BridgeError};
+use url::Url;
+
+pub mod memory;
+mod proxy;
+pub mod resolve;
+#[cfg(feature = "stdio-transport")]
+pub mod stdio;
+pub mod tcp;
+
+/// A framed, bidirectional message stream; anything that is both a `Stream` of incoming
+/// messages and a `Sink` for outgoing ones qualifies.
+pub trait MessageStream: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Send {}
+
+impl<T> MessageStream for T where T: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Send {}
+
+pub type Connection = Pin<Box<dyn MessageStream>>;
+
+/// Box any [`MessageStream`] into a [`Connection`].
+pub fn connection<S: MessageStream + 'static>(stream: S) -> Connection {
+    Box::pin(stream)
+}
+
+/// Frame a byte stream as newline-delimited JSON: each text frame is written as one line and
+/// each line read arrives as a text frame; a Close frame shuts down `write`. Other frame
+/// kinds are skipped. Use it for pipes, serial ports, and the like.
+pub fn lines<R, W>(read: R, write: W) -> Connection
+where
+    R: AsyncRead + Send + 'static,
+    W: AsyncWrite + Send + Unpin + 'static,
+{
+    let incoming = stream::unfold(Box::pin(BufReader::new(read)).lines(), |mut lines| async move {
+        match lines.next_line().await {
+            Ok(Some(line)) => Some((Ok(Message::Text(line.into())), lines)),
+            Ok(None) => None,
+            Err(e) => Some((Err(WsError::Io(e)), lines)),
+        }
+    });
+    let outgoing = sink::unfold(write, |mut write, msg: Message| async move {
+        match msg {
+            Message::Text(txt) => {
+                write.write_all(txt.as_bytes()).await?;
+                write.write_all(b"\n").await?;
+                write.flush().await?;
+            }
+            Message::Close(_) => write.shutdown().await?,
+            _ => {}
+        }
+        Ok::<_, WsError>(write)
+    });
+    connection(Duplex { stream: Box::pin(incoming), sink: Box::pin(outgoing) })
+}
+
+/// Joins a separate read stream and write sink into one [`MessageStream`].
+struct Duplex<St, Si> {
+    stream: Pin<Box<St>>,
+    sink: Pin<Box<Si>>,
+}
+
+impl<St: Stream, Si> Stream for Duplex<St, Si> {
+    type Item = St::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<St::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
+
+impl<St, Si: Sink<Message>> Sink<Message> for Duplex<St, Si> {
+    type Error = Si::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        self.sink.as_mut().poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, msg: Message) -> Result<(), Si::Error> {
+        self.sink.as_mut().start_send(msg)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        self.sink.as_mut().poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Si::Error>> {
+        self.sink.as_mut().poll_close(cx)
+    }
+}
+
+/// Opens a [`Connection`] to `url` for each connection attempt.
+pub trait Transport: Send + Sync {
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Connection, BridgeError>>;
+}
+
+/// `ws://` (and, with the `rustls` or `native-tls` feature, `wss://`) via tokio-tungstenite and the system
+/// resolver. The client's own WebSocket connections work the same way but resolve through
+/// [`resolve`], honoring overrides and [`BridgeClient::set_resolver`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WebSocketTransport;
+
+impl Transport for WebSocketTransport {
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Connection, BridgeError>> {
+        Box::pin(async move {
+            let (ws, _) = tokio_tungstenite::connect_async(url).await?;
+            Ok(connection(ws))
+        })
+    }
+}
+
+impl BridgeClient {
+    /// Open connections with `transport` instead of picking one from the URL scheme.
+    /// `unix://` URLs still attach to a local broker.
+    pub fn set_transport<T>(&self, transport: T)
+    where
+        T: Transport + 'static,
+    {
+        *self.transport.lock().unwrap() = Some(Arc::new(transport));
+    }
+
+    pub(crate) async fn open_connection(&self) -> Result<Connection, BridgeError> {
+        self.open_connection_to(&self.url()).await
+    }
+
+    pub(crate) async fn open_connection_to(&self, url: &str) -> Result<Connection, BridgeError> {
+        let custom = self.transport.lock().unwrap().clone();
+        match custom {
+            Some(transport) => transport.connect(url).await,
+            None if url.starts_with("tcp://") => {
+                let (read, write) = self.dial(&Url::parse(url)?).await?.into_split();
+                Ok(lines(read, write))
+            }
+            #[cfg(feature = "stdio-transport")]
+            None if url.starts_with("stdio://") => stdio::StdioTransport.connect(url).await,
+            None => {
+                let parsed = Url::parse(url)?;
+                let request = self.handshake_request(url)?;
+                #[cfg(feature = "rustls")]
+                if let (Some(tls), "wss") = (&self.cfg.tls, parsed.scheme()) {
+                    let connector = tls.connector()?;
+                    let stream = self.dial(&parsed).await?;
+                    let stream = tls.connect(&connector, parsed.host_str().unwrap_or_default(), stream).await?;
+                    let (ws, _) = tokio_tungstenite::client_async(request, stream).await?;
+                    return Ok(connection(ws));
+                }
+                let stream = self.dial(&parsed).await?;
+                #[cfg(any(feature = "rustls", feature = "native-tls"))]
+                let (ws, _) = tokio_tungstenite::client_async_tls(request, stream).await?;
+                #[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+                let (ws, _) = match parsed.scheme() {
+                    "wss" => return Err(WsError::Url(tokio_tungstenite::tungstenite::error::UrlError::TlsFeatureNotEnabled).into()),
+                    _ => tokio_tungstenite::client_async(request, stream).await?,
+                };
+                Ok(connection(ws))
+            }
+        }
+    }
+
+    /// The upgrade request for `url`, carrying `BridgeConfig::headers`.
+    #[allow(clippy::result_large_err)]
+    fn handshake_request(&self, url: &str) -> Result<Request, BridgeError> {
+        let mut request = url.into_client_request()?;
+        for (name, value) in &self.cfg.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| WsError::HttpFormat(http::Error::from(e)))?;
+            let value = HeaderValue::from_str(value).map_err(|e| WsError::HttpFormat(http::Error::from(e)))?;
+            request.headers_mut().append(name, value);
+        }
+        Ok(request)
+    }
+}
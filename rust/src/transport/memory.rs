@@ -0,0 +1,94 @@
+//! In-process transport for tests: the client connects to a [`MemoryHost`] through channels,
+//! so control handlers and event flow can be exercised without sockets, and under
+//! `tokio::time::pause()` without waiting on the wall clock.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::future::BoxFuture;
+use futures_util::{Sink, Stream};
+use tokio::sync::mpsc;
+
+use super::{connection, Connection, Message, Transport, WsError};
+use crate::BridgeError;
+
+/// A connected [`MemoryTransport`] and the [`MemoryHost`] its connections arrive at.
+pub fn pair() -> (MemoryTransport, MemoryHost) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (MemoryTransport { hosts: tx }, MemoryHost { incoming: rx })
+}
+
+/// Client side; pass it to [`BridgeClient::set_transport`](crate::BridgeClient::set_transport).
+/// Each connection attempt creates a fresh [`MemoryStream`] pair. The URL is ignored.
+pub struct MemoryTransport {
+    hosts: mpsc::UnboundedSender<MemoryStream>,
+}
+
+impl Transport for MemoryTransport {
+    fn connect<'a>(&'a self, _url: &'a str) -> BoxFuture<'a, Result<Connection, BridgeError>> {
+        Box::pin(async move {
+            let (client, host) = MemoryStream::pair();
+            self.hosts
+                .send(host)
+                .map_err(|_| BridgeError::Io(std::io::ErrorKind::ConnectionRefused.into()))?;
+            Ok(connection(client))
+        })
+    }
+}
+
+/// Host side: accepts the client's connections, in order.
+pub struct MemoryHost {
+    incoming: mpsc::UnboundedReceiver<MemoryStream>,
+}
+
+impl MemoryHost {
+    /// The next connection the client opens; `None` once the transport is dropped.
+    pub async fn accept(&mut self) -> Option<MemoryStream> {
+        self.incoming.recv().await
+    }
+}
+
+/// One end of an in-memory connection. Read with `StreamExt::next`, write with
+/// `SinkExt::send`; the stream ends when the other end closes or is dropped.
+pub struct MemoryStream {
+    tx: Option<mpsc::UnboundedSender<Message>>,
+    rx: mpsc::UnboundedReceiver<Message>,
+}
+
+impl MemoryStream {
+    fn pair() -> (Self, Self) {
+        let (a_tx, a_rx) = mpsc::unbounded_channel();
+        let (b_tx, b_rx) = mpsc::unbounded_channel();
+        (Self { tx: Some(a_tx), rx: b_rx }, Self { tx: Some(b_tx), rx: a_rx })
+    }
+}
+
+impl Stream for MemoryStream {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx).map(|msg| msg.map(Ok))
+    }
+}
+
+impl Sink<Message> for MemoryStream {
+    type Error = WsError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, msg: Message) -> Result<(), WsError> {
+        let tx = self.tx.as_ref().ok_or(WsError::AlreadyClosed)?;
+        tx.send(msg).map_err(|_| WsError::ConnectionClosed)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        self.tx = None;
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,140 @@
+//! Egress through a proxy for the built-in transports (see
+//! [`BridgeConfig::proxy`](crate::BridgeConfig::proxy)): `http://` proxies get an HTTP
+//! `CONNECT`, `socks5://` (or `socks5h://`) ones a SOCKS5 `CONNECT`, with basic or
+//! username/password auth taken from the proxy URL. `http://` and `socks5h://` proxies get the
+//! target name unresolved, `socks5://` ones an address the client resolved; TLS and the
+//! WebSocket upgrade then run through the tunnel.
+
+use std::io;
+
+use base64::Engine;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::{Host, Url};
+
+/// Used when a `socks5://` proxy URL has no port.
+pub(crate) const SOCKS5_PORT: u16 = 1080;
+
+/// Turn `stream`, connected to `proxy`, into a tunnel to `host:port`.
+pub(crate) async fn tunnel(proxy: &Url, mut stream: TcpStream, host: &Host<&str>, port: u16) -> io::Result<TcpStream> {
+    let auth = match proxy.username() {
+        "" => None,
+        user => Some((decode(user), decode(proxy.password().unwrap_or_default()))),
+    };
+    match proxy.scheme() {
+        "http" => http_connect(&mut stream, host, port, auth).await?,
+        "socks5" | "socks5h" => socks5_connect(&mut stream, host, port, auth).await?,
+        other => return Err(invalid(format!("unsupported proxy scheme {other}"))),
+    }
+    Ok(stream)
+}
+
+async fn http_connect(stream: &mut TcpStream, host: &Host<&str>, port: u16, auth: Option<(String, String)>) -> io::Result<()> {
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if let Some((user, pass)) = auth {
+        let token = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // Read byte by byte so nothing past the header belongs to the tunnel's first read.
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 8 * 1024 {
+            return Err(invalid("proxy response header too long"));
+        }
+        head.push(stream.read_u8().await?);
+    }
+    let status_line = String::from_utf8_lossy(&head);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(io::Error::new(io::ErrorKind::ConnectionRefused, format!("proxy refused CONNECT: {status_line}"))),
+    }
+}
+
+async fn socks5_connect(stream: &mut TcpStream, host: &Host<&str>, port: u16, auth: Option<(String, String)>) -> io::Result<()> {
+    let methods: &[u8] = if auth.is_some() { &[0x00, 0x02] } else { &[0x00] };
+    stream.write_all(&[&[0x05, methods.len() as u8], methods].concat()).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    match (reply, &auth) {
+        ([0x05, 0x00], _) => {}
+        ([0x05, 0x02], Some((user, pass))) => {
+            let (user, pass) = (user.as_bytes(), pass.as_bytes());
+            if user.len() > 255 || pass.len() > 255 {
+                return Err(invalid("proxy credentials too long for SOCKS5"));
+            }
+            stream.write_all(&[&[0x01, user.len() as u8], user, &[pass.len() as u8], pass].concat()).await?;
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0x00 {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "SOCKS5 proxy rejected the credentials"));
+            }
+        }
+        _ => return Err(io::Error::new(io::ErrorKind::PermissionDenied, "SOCKS5 proxy offered no usable auth method")),
+    }
+
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host {
+        Host::Ipv4(ip) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Host::Ipv6(ip) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        Host::Domain(name) => {
+            let name = name.as_bytes();
+            if name.len() > 255 {
+                return Err(invalid("host name too long for SOCKS5"));
+            }
+            request.extend_from_slice(&[0x03, name.len() as u8]);
+            request.extend_from_slice(name);
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await?;
+    if head[1] != 0x00 {
+        return Err(io::Error::new(io::ErrorKind::ConnectionRefused, format!("SOCKS5 CONNECT failed (reply {})", head[1])));
+    }
+    // Skip the bound address and port.
+    let addr_len = match head[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => stream.read_u8().await? as usize,
+        _ => return Err(invalid("SOCKS5 reply with unknown address type")),
+    };
+    let mut rest = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut rest).await?;
+    Ok(())
+}
+
+/// Percent-decode a URL userinfo component.
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.into())
+}
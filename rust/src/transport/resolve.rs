@@ -0,0 +1,99 @@
+//! Name resolution for the built-in `ws://`, `wss://`, and `tcp://` transports. Host names are
+//! looked up again on every connection attempt, so DNS-based failover takes effect on the
+//! next reconnect; [`BridgeConfig::resolve_overrides`](crate::BridgeConfig::resolve_overrides)
+//! and [`BridgeClient::set_resolver`] cover split-horizon setups. Behind a `socks5://` proxy
+//! names are still resolved here; `socks5h://` and `http://` proxies resolve them themselves.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use tokio::net::TcpStream;
+use url::{Host, Url};
+
+use crate::{BridgeClient, BridgeError};
+
+/// Looks up the addresses for a host name; tried in order until one accepts.
+pub trait Resolve: Send + Sync {
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>>;
+}
+
+/// The operating system's resolver, via `getaddrinfo`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+        Box::pin(async move { Ok(tokio::net::lookup_host((host, port)).await?.collect()) })
+    }
+}
+
+impl BridgeClient {
+    /// Resolve host names with `resolver` instead of the system resolver. Entries in
+    /// `resolve_overrides` still take precedence, and IP-literal URLs skip resolution.
+    pub fn set_resolver<R>(&self, resolver: R)
+    where
+        R: Resolve + 'static,
+    {
+        *self.resolver.lock().unwrap() = Some(Arc::new(resolver));
+    }
+
+    async fn resolve(&self, host: &Host<&str>, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let name = match host {
+            Host::Ipv4(ip) => return Ok(vec![SocketAddr::new(IpAddr::V4(*ip), port)]),
+            Host::Ipv6(ip) => return Ok(vec![SocketAddr::new(IpAddr::V6(*ip), port)]),
+            Host::Domain(name) => *name,
+        };
+        if let Some(ip) = self.cfg.resolve_overrides.get(name) {
+            return Ok(vec![SocketAddr::new(*ip, port)]);
+        }
+        let custom = self.resolver.lock().unwrap().clone();
+        match custom {
+            Some(resolver) => resolver.resolve(name, port).await,
+            None => SystemResolver.resolve(name, port).await,
+        }
+    }
+
+    /// Open a TCP stream to the host and port of `url`, resolving it afresh, through
+    /// `BridgeConfig::proxy` when one is set. A `socks5://` proxy is given the addresses
+    /// resolved here, tried in order; `socks5h://` and `http://` proxies get the name.
+    pub(crate) async fn dial(&self, url: &Url) -> Result<TcpStream, BridgeError> {
+        let host = url.host().ok_or(BridgeError::Url(url::ParseError::EmptyHost))?;
+        let port = url.port_or_known_default().ok_or(BridgeError::Url(url::ParseError::InvalidPort))?;
+        let Some(proxy) = &self.cfg.proxy else {
+            return self.connect_tcp(&host, port).await;
+        };
+        let proxy = Url::parse(proxy)?;
+        let proxy_host = proxy.host().ok_or(BridgeError::Url(url::ParseError::EmptyHost))?;
+        let proxy_port = proxy.port_or_known_default().unwrap_or(super::proxy::SOCKS5_PORT);
+        if proxy.scheme() != "socks5" {
+            let stream = self.connect_tcp(&proxy_host, proxy_port).await?;
+            return Ok(super::proxy::tunnel(&proxy, stream, &host, port).await?);
+        }
+        let mut last = io::Error::new(io::ErrorKind::NotFound, format!("{} did not resolve", host));
+        for addr in self.resolve(&host, port).await? {
+            let target = match addr.ip() {
+                IpAddr::V4(ip) => Host::Ipv4(ip),
+                IpAddr::V6(ip) => Host::Ipv6(ip),
+            };
+            let stream = self.connect_tcp(&proxy_host, proxy_port).await?;
+            match super::proxy::tunnel(&proxy, stream, &target, addr.port()).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last = e,
+            }
+        }
+        Err(last.into())
+    }
+
+    async fn connect_tcp(&self, host: &Host<&str>, port: u16) -> Result<TcpStream, BridgeError> {
+        let mut last = io::Error::new(io::ErrorKind::NotFound, format!("{} did not resolve", host));
+        for addr in self.resolve(host, port).await? {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last = e,
+            }
+        }
+        Err(last.into())
+    }
+}
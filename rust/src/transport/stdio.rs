@@ -0,0 +1,20 @@
+//! `stdio://`: the protocol over this process's stdin/stdout, one JSON frame per line, so a
+//! parent tool that spawned the process can bridge it without any network setup (like an
+//! LSP server). Nothing else may write to stdout: don't combine it with
+//! [`StdoutSink`](crate::StdoutSink) or [`capture_stdio`](crate::BridgeClient::capture_stdio).
+
+use futures_util::future::BoxFuture;
+
+use super::{lines, Connection, Transport};
+use crate::BridgeError;
+
+/// Newline-delimited JSON over stdin/stdout (see [`lines`]). Selected automatically for
+/// `stdio://` URLs. Once stdin reaches EOF the parent is gone, so later reconnects fail fast.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdioTransport;
+
+impl Transport for StdioTransport {
+    fn connect<'a>(&'a self, _url: &'a str) -> BoxFuture<'a, Result<Connection, BridgeError>> {
+        Box::pin(async move { Ok(lines(tokio::io::stdin(), tokio::io::stdout())) })
+    }
+}
@@ -0,0 +1,22 @@
+//! `tcp://host:port`: the same JSON frames, one per line over a plain TCP stream, for
+//! embedded hosts without a WebSocket stack.
+
+use futures_util::future::BoxFuture;
+use tokio::net::TcpStream;
+
+use super::{lines, Connection, Transport};
+use crate::BridgeError;
+
+/// Newline-delimited JSON over TCP (see [`lines`]). Selected automatically for `tcp://` URLs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TcpTransport;
+
+impl Transport for TcpTransport {
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Connection, BridgeError>> {
+        Box::pin(async move {
+            let addr = url.strip_prefix("tcp://").unwrap_or(url).trim_end_matches('/');
+            let (read, write) = TcpStream::connect(addr).await?.into_split();
+            Ok(lines(read, write))
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::{now_ms, BridgeClient};
+
+/// Last time the application called [`BridgeClient::heartbeat_app`]; `None` until the first pet.
+pub(crate) type WatchdogState = Arc<Mutex<Option<Instant>>>;
+
+impl BridgeClient {
+    /// Pet the watchdog. Once called, the application must keep calling it within
+    /// `watchdog_timeout_ms` or a `hang_suspected` error is reported.
+    pub fn heartbeat_app(&self) {
+        *self.watchdog.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Runs on a plain OS thread so it keeps working when the async runtime is the thing
+    /// that is stuck. Exits once every client handle has been dropped.
+    pub(crate) fn spawn_watchdog(&self, timeout: Duration) {
+        let (client, handles) = self.detach();
+        let check_every = std::cmp::max(timeout / 4, Duration::from_millis(10));
+        std::thread::Builder::new()
+            .name("aria-bridge-watchdog".into())
+            .spawn(move || {
+                let mut reported = false;
+                while handles.strong_count() > 0 {
+                    std::thread::sleep(check_every);
+                    let Some(last) = *client.watchdog.lock().unwrap() else { continue };
+                    let silent = last.elapsed();
+                    if silent < timeout {
+                        reported = false;
+                    } else if !reported {
+                        reported = true;
+                        let dump = client.dump_tasks();
+                        client.enqueue(json!({
+                            "type":"error",
+                            "level":"error",
+                            "kind":"hang_suspected",
+                            "message":format!("hang suspected: no app heartbeat for {}ms", silent.as_millis()),
+                            "silentMs":silent.as_millis() as u64,
+                            "threads":dump["threads"],
+                            "tasks":dump["tasks"],
+                            "timestamp":now_ms()
+                        }));
+                    }
+                }
+            })
+            .expect("spawn watchdog thread");
+    }
+}
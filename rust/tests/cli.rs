@@ -0,0 +1,162 @@
+#![cfg(feature = "cli")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+/// Accepts one line-protocol connection and returns every frame up to the goodbye event.
+fn host(listener: TcpListener) -> std::thread::JoinHandle<Vec<Value>> {
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut write = stream.try_clone().unwrap();
+        let mut seen: Vec<Value> = Vec::new();
+        for line in BufReader::new(stream).lines() {
+            let v: Value = serde_json::from_str(&line.unwrap()).unwrap();
+            if v["type"] == "auth" {
+                write.write_all(b"{\"type\":\"auth_success\",\"role\":\"bridge\"}\n").unwrap();
+            }
+            let done = v["type"] == "shutdown";
+            seen.push(v);
+            if done {
+                break;
+            }
+        }
+        seen
+    })
+}
+
+#[test]
+fn stdin_lines_become_console_events() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("tcp://{}", listener.local_addr().unwrap());
+    let host = host(listener);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aria-bridge"))
+        .args(["--project", "cli", "--level=warn", "--json"])
+        .env("ARIA_BRIDGE_URL", &url)
+        .env("ARIA_BRIDGE_SECRET", "from-env")
+        .stdin(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"first line\n{\"type\":\"deploy\",\"version\":\"1.2\"}\n{\"no\":\"type\"}\n").unwrap();
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+
+    let seen = host.join().unwrap();
+    assert_eq!(seen[0]["secret"], "from-env");
+    assert_eq!(seen[1]["projectId"], "cli");
+    let consoles: Vec<&Value> = seen.iter().filter(|v| v["type"] == "console").collect();
+    assert_eq!(consoles.len(), 2);
+    assert_eq!(consoles[0]["message"], "first line");
+    assert_eq!(consoles[0]["level"], "warn");
+    assert_eq!(consoles[1]["message"], "{\"no\":\"type\"}");
+    assert_eq!(seen.iter().find(|v| v["type"] == "deploy").unwrap()["version"], "1.2");
+}
+
+#[test]
+fn unknown_flags_are_rejected() {
+    let out = Command::new(env!("CARGO_BIN_EXE_aria-bridge")).arg("--nope").output().unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("unknown argument: --nope"));
+}
+
+#[test]
+fn tail_follows_files_across_rotation() {
+    let dir = std::env::temp_dir().join(format!("aria-cli-tail-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("app.log");
+    let other = dir.join("worker.log");
+    std::fs::write(&log, "already there\n").unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("tcp://{}", listener.local_addr().unwrap());
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aria-bridge"))
+        .args(["--url", &url, "tail"])
+        .args([&log, &other])
+        .spawn()
+        .unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5))).unwrap();
+    let mut write = stream.try_clone().unwrap();
+    let mut lines = BufReader::new(stream).lines();
+    let mut next = || serde_json::from_str::<Value>(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(next()["type"], "auth");
+    write.write_all(b"{\"type\":\"auth_success\",\"role\":\"bridge\"}\n").unwrap();
+    assert_eq!(next()["type"], "hello");
+
+    let append = |path: &std::path::Path, text: &str| {
+        let mut f = std::fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        f.write_all(text.as_bytes()).unwrap();
+    };
+    append(&log, "before rotation\n");
+    std::thread::sleep(std::time::Duration::from_millis(400));
+    std::fs::rename(&log, dir.join("app.log.1")).unwrap();
+    append(&log, "after rotation\r\n");
+    append(&other, "new file\n");
+
+    let mut consoles = Vec::new();
+    while consoles.len() < 3 {
+        let v = next();
+        if v["type"] == "console" {
+            consoles.push((v["file"].as_str().unwrap().to_string(), v["message"].as_str().unwrap().to_string()));
+        }
+    }
+    child.kill().unwrap();
+    let _ = child.wait();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // The two files are polled independently, so only order within a file is guaranteed.
+    let from = |path: &std::path::Path| -> Vec<&str> {
+        consoles.iter().filter(|(file, _)| *file == path.display().to_string()).map(|(_, msg)| msg.as_str()).collect()
+    };
+    assert_eq!(from(&log), ["before rotation", "after rotation"]);
+    assert_eq!(from(&other), ["new file"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn tail_flushes_and_shuts_down_on_sigterm() {
+    let dir = std::env::temp_dir().join(format!("aria-cli-tail-term-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("app.log");
+    std::fs::write(&log, "").unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("tcp://{}", listener.local_addr().unwrap());
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aria-bridge")).args(["--url", &url, "tail"]).arg(&log).spawn().unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5))).unwrap();
+    let mut write = stream.try_clone().unwrap();
+    let mut lines = BufReader::new(stream).lines();
+    let mut next = || serde_json::from_str::<Value>(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(next()["type"], "auth");
+    write.write_all(b"{\"type\":\"auth_success\",\"role\":\"bridge\"}\n").unwrap();
+
+    let append = |text: &str| {
+        let mut f = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+        f.write_all(text.as_bytes()).unwrap();
+    };
+    append("running\n");
+    while next()["message"] != "running" {}
+    // Written just before the signal: still read, delivered, and followed by a clean close.
+    append("last words\n");
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+    let mut rest = Vec::new();
+    while rest.last().is_none_or(|v: &Value| v["type"] != "shutdown") {
+        rest.push(next());
+    }
+    drop((lines, write));
+    assert!(child.wait().unwrap().success());
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(rest.iter().any(|v| v["message"] == "last words"));
+}
+
+#[test]
+fn tail_needs_a_path() {
+    let out = Command::new(env!("CARGO_BIN_EXE_aria-bridge")).arg("tail").output().unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("tail needs at least one path"));
+}
@@ -0,0 +1,46 @@
+//! Separate test binary: the early-boot buffer is process-global, so clients created by
+//! other tests would drain it.
+use aria_bridge_client::{early_error, early_log, BridgeClient, BridgeConfig, EARLY_BUFFER_LIMIT};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+#[tokio::test]
+async fn early_events_flow_through_first_client() {
+    early_log!("info", "parsing args: {}", "--verbose");
+    for i in 0..EARLY_BUFFER_LIMIT {
+        early_log!("debug", "filler {}", i);
+    }
+    early_error!("config not found: {}", "/etc/app.toml");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let host = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        let mut seen = Vec::new();
+        while let Some(Ok(Message::Text(txt))) = ws.next().await {
+            let v: Value = serde_json::from_str(&txt).unwrap();
+            if v["type"] == "auth" {
+                ws.send(Message::Text(r#"{"type":"auth_success","role":"bridge"}"#.into())).await.unwrap();
+            }
+            seen.push(v);
+            if seen.iter().any(|v| v["message"].as_str().is_some_and(|m| m.contains("drop count"))) {
+                return seen;
+            }
+        }
+        seen
+    });
+
+    let client = BridgeClient::new(BridgeConfig { url: format!("ws://{}", addr), ..BridgeConfig::default() });
+    let run = tokio::spawn(async move { client.run_with_reconnect().await });
+    let seen = tokio::time::timeout(std::time::Duration::from_secs(2), host).await.unwrap().unwrap();
+    run.abort();
+
+    let err = seen.iter().find(|v| v["type"] == "error").unwrap();
+    assert_eq!(err["message"], "config not found: /etc/app.toml");
+    assert_eq!(err["early"], true);
+    assert!(!seen.iter().any(|v| v["message"] == "parsing args: --verbose"));
+    assert!(seen.iter().any(|v| v["message"].as_str().unwrap_or("").contains("drop count=2")));
+}
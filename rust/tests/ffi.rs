@@ -0,0 +1,61 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use aria_bridge_client::ffi::*;
+use serde_json::Value;
+
+unsafe extern "C" fn answer(request_json: *const c_char, user_data: *mut c_void) -> *const c_char {
+    let request: Value = serde_json::from_str(CStr::from_ptr(request_json).to_str().unwrap()).unwrap();
+    assert_eq!(request["action"], "echo");
+    (*(user_data as *const AtomicUsize)).fetch_add(1, Ordering::SeqCst);
+    c"{\"from\":\"c\"}".as_ptr()
+}
+
+#[test]
+fn c_abi_sends_events_answers_control_and_shuts_down() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = CString::new(format!("tcp://{}", listener.local_addr().unwrap())).unwrap();
+    let host = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut write = stream.try_clone().unwrap();
+        let mut seen: Vec<Value> = Vec::new();
+        for line in BufReader::new(stream).lines() {
+            let v: Value = serde_json::from_str(&line.unwrap()).unwrap();
+            if v["type"] == "auth" {
+                write.write_all(b"{\"type\":\"auth_success\",\"role\":\"bridge\"}\n").unwrap();
+                write.write_all(b"{\"type\":\"control_request\",\"id\":\"c1\",\"action\":\"echo\",\"args\":{}}\n").unwrap();
+            }
+            let done = v["type"] == "shutdown";
+            seen.push(v);
+            if done {
+                break;
+            }
+        }
+        seen
+    });
+
+    let calls = AtomicUsize::new(0);
+    unsafe {
+        let bridge = aria_bridge_new(url.as_ptr(), c"s3cret".as_ptr(), c"app".as_ptr());
+        assert!(!bridge.is_null());
+        let user_data = &calls as *const AtomicUsize as *mut c_void;
+        assert_eq!(aria_bridge_on_control(bridge, Some(answer), user_data), 0);
+        assert_eq!(aria_bridge_send_console(bridge, c"info".as_ptr(), c"from C".as_ptr()), 0);
+        assert_eq!(aria_bridge_send_console(bridge, std::ptr::null(), c"no level".as_ptr()), -1);
+        while calls.load(Ordering::SeqCst) == 0 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(aria_bridge_shutdown(bridge, 2000), 0);
+    }
+
+    let seen = host.join().unwrap();
+    assert_eq!(seen[0]["secret"], "s3cret");
+    assert_eq!(seen.iter().find(|v| v["type"] == "control_result").unwrap()["result"]["from"], "c");
+    assert_eq!(seen[1]["projectId"], "app");
+    assert!(seen.iter().any(|v| v["type"] == "console" && v["message"] == "from C"));
+    assert_eq!(seen.last().unwrap()["type"], "shutdown");
+}
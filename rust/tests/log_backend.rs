@@ -0,0 +1,48 @@
+//! Separate test binary: `BridgeLogger::init` sets the process-global logger.
+#![cfg(feature = "log")]
+
+use aria_bridge_client::{BridgeClient, BridgeConfig, BridgeLogger};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+#[tokio::test]
+async fn log_records_become_console_events() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let host = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        let mut seen = Vec::new();
+        while let Some(Ok(Message::Text(txt))) = ws.next().await {
+            let v: Value = serde_json::from_str(&txt).unwrap();
+            if v["type"] == "auth" {
+                ws.send(Message::Text(r#"{"type":"auth_success","role":"bridge"}"#.into())).await.unwrap();
+            }
+            let done = v["message"] == "last";
+            seen.push(v);
+            if done {
+                return seen;
+            }
+        }
+        seen
+    });
+
+    let client = BridgeClient::new(BridgeConfig { url: format!("ws://{}", addr), ..BridgeConfig::default() });
+    BridgeLogger::init(client.clone()).unwrap();
+    log::info!(target: "app::http", "listening on {}", 8080);
+    log::debug!("filtered out");
+    log::warn!("last");
+
+    let run = tokio::spawn(async move { client.run_with_reconnect().await });
+    let seen = tokio::time::timeout(std::time::Duration::from_secs(2), host).await.unwrap().unwrap();
+    run.abort();
+
+    let info = seen.iter().find(|v| v["message"] == "listening on 8080").unwrap();
+    assert_eq!(info["type"], "console");
+    assert_eq!(info["level"], "info");
+    assert_eq!(info["target"], "app::http");
+    assert_eq!(seen.iter().find(|v| v["message"] == "last").unwrap()["level"], "warn");
+    assert!(!seen.iter().any(|v| v["message"] == "filtered out"));
+}
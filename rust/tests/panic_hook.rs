@@ -0,0 +1,44 @@
+//! Separate test binary: the panic hook is process-global.
+use aria_bridge_client::{BridgeClient, BridgeConfig, ConnectionState};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn panics_are_reported_as_error_events() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let host = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        while let Some(Ok(Message::Text(txt))) = ws.next().await {
+            let v: Value = serde_json::from_str(&txt).unwrap();
+            if v["type"] == "auth" {
+                ws.send(Message::Text(r#"{"type":"auth_success","role":"bridge"}"#.into())).await.unwrap();
+            }
+            if v["kind"] == "panic" {
+                return Some(v);
+            }
+        }
+        None
+    });
+
+    let client = BridgeClient::new(BridgeConfig { url: format!("ws://{}", addr), ..BridgeConfig::default() });
+    client.install_panic_hook();
+    let mut state = client.state();
+    let runner = client.clone();
+    let run = tokio::spawn(async move { runner.run_with_reconnect().await });
+    state.wait_for(|s| *s == ConnectionState::Connected).await.unwrap();
+
+    let worker = std::thread::Builder::new().name("worker".into()).spawn(|| panic!("index {} out of range", 7)).unwrap();
+    assert!(worker.join().is_err());
+
+    let ev = tokio::time::timeout(std::time::Duration::from_secs(2), host).await.unwrap().unwrap().unwrap();
+    run.abort();
+    assert_eq!(ev["type"], "error");
+    assert_eq!(ev["message"], "panic: index 7 out of range");
+    assert_eq!(ev["thread"], "worker");
+    assert!(ev["location"]["file"].as_str().unwrap().ends_with("panic_hook.rs"));
+    assert!(ev["backtrace"].is_string());
+}
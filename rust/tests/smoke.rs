@@ -12,6 +12,7 @@ async fn heartbeat_and_reconnect() {
         backoff_initial_ms: 50,
         backoff_max_ms: 200,
         buffer_limit: 200,
+        ..BridgeConfig::default()
     };
     let client = BridgeClient::new(cfg);
     // Run briefly to cover heartbeat/reconnect loop; abort after short duration
@@ -0,0 +1,58 @@
+//! Separate test binary: capture redirects the process's stdout/stderr.
+#![cfg(all(unix, feature = "stdio-capture"))]
+
+use std::io::Write;
+
+use aria_bridge_client::{BridgeClient, BridgeConfig};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+#[tokio::test]
+async fn printed_lines_become_console_events() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let host = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        let mut seen = Vec::new();
+        while let Some(Ok(Message::Text(txt))) = ws.next().await {
+            let v: Value = serde_json::from_str(&txt).unwrap();
+            if v["type"] == "auth" {
+                ws.send(Message::Text(r#"{"type":"auth_success","role":"bridge"}"#.into())).await.unwrap();
+            }
+            seen.push(v);
+            if seen.iter().filter(|v| v["stream"].is_string()).count() == 2 {
+                return seen;
+            }
+        }
+        seen
+    });
+
+    let client = BridgeClient::new(BridgeConfig { url: format!("ws://{}", addr), ..BridgeConfig::default() });
+    client.capture_stdio().unwrap();
+    assert!(client.capture_stdio().is_err());
+    // Write to the fds directly; libtest swallows `println!` output.
+    writeln!(std::io::stdout(), "listening on 8080").unwrap();
+    std::io::stdout().flush().unwrap();
+    writeln!(std::io::stderr(), "deprecated flag").unwrap();
+
+    let run = tokio::spawn(async move { client.run_with_reconnect().await });
+    let seen = tokio::time::timeout(std::time::Duration::from_secs(2), host).await.unwrap().unwrap();
+    run.abort();
+
+    let out = seen.iter().find(|v| v["stream"] == "stdout").unwrap();
+    assert_eq!(out["type"], "console");
+    assert_eq!(out["level"], "info");
+    assert_eq!(out["message"], "listening on 8080");
+    let err = seen.iter().find(|v| v["stream"] == "stderr").unwrap();
+    assert_eq!(err["level"], "warn");
+    assert_eq!(err["message"], "deprecated flag");
+}
+
+#[test]
+fn refuses_while_stdout_carries_the_stdio_transport() {
+    let client = BridgeClient::new(BridgeConfig { url: "stdio://".into(), ..BridgeConfig::default() });
+    assert_eq!(client.capture_stdio().unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+}
@@ -0,0 +1,194 @@
+#![cfg(feature = "rustls")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use aria_bridge_client::{BridgeClient, BridgeConfig, BridgeError, ConnectionState, TlsConfig, CLOSE_GOING_AWAY};
+use futures_util::{SinkExt, StreamExt};
+use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, IsCa, KeyPair, PublicKeyData};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::Message;
+
+/// An internal CA with a `localhost` server certificate and a client certificate.
+struct Pki {
+    ca_pem: String,
+    server_chain: Vec<CertificateDer<'static>>,
+    server_key: PrivateKeyDer<'static>,
+    client_pem: String,
+    client_key_pem: String,
+    roots: RootCertStore,
+    /// `sha256/` pin of the server's public key.
+    server_pin: String,
+}
+
+fn pin(key: &KeyPair) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, &key.subject_public_key_info());
+    format!("sha256/{}", base64::engine::general_purpose::STANDARD.encode(digest))
+}
+
+fn pki() -> Pki {
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = CertifiedIssuer::self_signed(ca_params, KeyPair::generate().unwrap()).unwrap();
+    let server_key = KeyPair::generate().unwrap();
+    let server = CertificateParams::new(vec!["localhost".to_string()]).unwrap().signed_by(&server_key, &ca).unwrap();
+    let client_key = KeyPair::generate().unwrap();
+    let client = CertificateParams::new(vec!["bridge".to_string()]).unwrap().signed_by(&client_key, &ca).unwrap();
+    let mut roots = RootCertStore::empty();
+    roots.add(ca.der().clone()).unwrap();
+    Pki {
+        ca_pem: ca.pem(),
+        server_chain: vec![server.der().clone()],
+        server_key: PrivatePkcs8KeyDer::from(server_key.serialize_der()).into(),
+        client_pem: client.pem(),
+        client_key_pem: client_key.serialize_pem(),
+        roots,
+        server_pin: pin(&server_key),
+    }
+}
+
+/// A `wss://` host that requires a client certificate from the CA; reports each bridge that
+/// gets through the handshake and authenticates.
+async fn start_host(pki: &Pki) -> (String, mpsc::UnboundedReceiver<Value>) {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(pki.roots.clone()), provider.clone()).build().unwrap();
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(pki.server_chain.clone(), pki.server_key.clone_key())
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("wss://localhost:{}", listener.local_addr().unwrap().port());
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let (acceptor, tx) = (acceptor.clone(), tx.clone());
+            tokio::spawn(async move {
+                let Ok(tls) = acceptor.accept(stream).await else { return };
+                let Ok(mut ws) = tokio_tungstenite::accept_async(tls).await else { return };
+                while let Some(Ok(Message::Text(txt))) = ws.next().await {
+                    let v: Value = serde_json::from_str(&txt).unwrap();
+                    if v["type"] == "auth" {
+                        ws.send(Message::Text(json!({"type":"auth_success"}).to_string().into())).await.unwrap();
+                        let _ = tx.send(v);
+                    }
+                }
+            });
+        }
+    });
+    (url, rx)
+}
+
+#[tokio::test]
+async fn mutual_tls_with_a_private_ca() {
+    let pki = pki();
+    let (url, mut authed) = start_host(&pki).await;
+
+    let tls = TlsConfig {
+        ca_pem: Some(pki.ca_pem.clone().into_bytes()),
+        native_roots: false,
+        client_cert_pem: Some(pki.client_pem.clone().into_bytes()),
+        client_key_pem: Some(pki.client_key_pem.clone().into_bytes()),
+        ..TlsConfig::default()
+    };
+    let client = BridgeClient::new(BridgeConfig { url: url.clone(), tls: Some(tls), ..BridgeConfig::default() });
+    let mut state = client.state();
+    let runner = client.clone();
+    let run = tokio::spawn(async move { runner.run_with_reconnect().await });
+    let connected =
+        tokio::time::timeout(Duration::from_secs(5), state.wait_for(|s| *s == ConnectionState::Connected)).await.is_ok();
+    assert!(connected);
+    assert_eq!(authed.recv().await.unwrap()["secret"], "dev-secret");
+    client.close(CLOSE_GOING_AWAY, "bye");
+    assert!(run.await.unwrap().is_ok());
+
+    // Trusting the CA but presenting no certificate: the host refuses the handshake.
+    let tls = TlsConfig { ca_pem: Some(pki.ca_pem.into_bytes()), native_roots: false, ..TlsConfig::default() };
+    let cfg = BridgeConfig { url, tls: Some(tls), max_reconnect_attempts: Some(1), ..BridgeConfig::default() };
+    let result = BridgeClient::new(cfg).run_with_reconnect().await;
+    assert!(matches!(result, Err(BridgeError::ReconnectExhausted { .. })), "{result:?}");
+    assert!(authed.try_recv().is_err());
+
+    // A key without its certificate is a configuration error.
+    let tls = TlsConfig { client_key_pem: Some(pki.client_key_pem.into_bytes()), ..TlsConfig::default() };
+    let cfg = BridgeConfig { url: "wss://localhost:1".into(), tls: Some(tls), max_reconnect_attempts: Some(1), ..BridgeConfig::default() };
+    match BridgeClient::new(cfg).run_with_reconnect().await {
+        Err(BridgeError::ReconnectExhausted { last, .. }) => assert!(matches!(*last, BridgeError::Tls(_))),
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn spki_pins_accept_matching_chains_and_reject_others() {
+    let pki = pki();
+    let (url, mut authed) = start_host(&pki).await;
+    let tls = |pins: Vec<String>| TlsConfig {
+        ca_pem: Some(pki.ca_pem.clone().into_bytes()),
+        native_roots: false,
+        client_cert_pem: Some(pki.client_pem.clone().into_bytes()),
+        client_key_pem: Some(pki.client_key_pem.clone().into_bytes()),
+        spki_pins: pins,
+        ..TlsConfig::default()
+    };
+
+    // Any one matching pin is enough.
+    let bogus = format!("sha256/{}", base64::engine::general_purpose::STANDARD.encode([0u8; 32]));
+    let client = BridgeClient::new(BridgeConfig { url: url.clone(), tls: Some(tls(vec![bogus.clone(), pki.server_pin.clone()])), ..BridgeConfig::default() });
+    let mut state = client.state();
+    let runner = client.clone();
+    let run = tokio::spawn(async move { runner.run_with_reconnect().await });
+    let connected =
+        tokio::time::timeout(Duration::from_secs(5), state.wait_for(|s| *s == ConnectionState::Connected)).await.is_ok();
+    assert!(connected);
+    authed.recv().await.unwrap();
+    client.close(CLOSE_GOING_AWAY, "bye");
+    assert!(run.await.unwrap().is_ok());
+
+    // No match: the loop stops before the WebSocket upgrade and reports the server's key.
+    let client = BridgeClient::new(BridgeConfig { url, tls: Some(tls(vec![bogus])), ..BridgeConfig::default() });
+    match tokio::time::timeout(Duration::from_secs(5), client.run_with_reconnect()).await.unwrap() {
+        Err(BridgeError::PinMismatch(key)) => assert_eq!(format!("sha256/{key}"), pki.server_pin),
+        other => panic!("unexpected {other:?}"),
+    }
+    assert!(authed.try_recv().is_err());
+}
+
+#[cfg(feature = "danger-insecure-tls")]
+#[tokio::test]
+async fn danger_accept_invalid_certs_skips_verification() {
+    let pki = pki();
+    let (url, mut authed) = start_host(&pki).await;
+    let tls = |danger_accept_invalid_certs| TlsConfig {
+        native_roots: false,
+        client_cert_pem: Some(pki.client_pem.clone().into_bytes()),
+        client_key_pem: Some(pki.client_key_pem.clone().into_bytes()),
+        danger_accept_invalid_certs,
+        ..TlsConfig::default()
+    };
+
+    // Nothing trusted: the host's certificate is rejected...
+    let cfg = BridgeConfig { url: url.clone(), tls: Some(tls(false)), max_reconnect_attempts: Some(1), ..BridgeConfig::default() };
+    let result = BridgeClient::new(cfg).run_with_reconnect().await;
+    assert!(matches!(result, Err(BridgeError::ReconnectExhausted { .. })), "{result:?}");
+
+    // ...unless verification is switched off.
+    let client = BridgeClient::new(BridgeConfig { url, tls: Some(tls(true)), ..BridgeConfig::default() });
+    let mut state = client.state();
+    let runner = client.clone();
+    let run = tokio::spawn(async move { runner.run_with_reconnect().await });
+    let connected =
+        tokio::time::timeout(Duration::from_secs(5), state.wait_for(|s| *s == ConnectionState::Connected)).await.is_ok();
+    assert!(connected);
+    authed.recv().await.unwrap();
+    client.close(CLOSE_GOING_AWAY, "bye");
+    assert!(run.await.unwrap().is_ok());
+}